@@ -2,24 +2,75 @@ pub struct ResourceAttributes;
 
 impl ResourceAttributes {
     pub const AWS_EC2: &'static str = "aws_ec2";
+    pub const BATCH_JOB_ID: &'static str = "gcp.batch.job.id";
+    pub const BATCH_TASK_ATTEMPT: &'static str = "gcp.batch.task.attempt";
+    pub const BATCH_TASK_COUNT: &'static str = "gcp.batch.task.count";
+    pub const BATCH_TASK_INDEX: &'static str = "gcp.batch.task.index";
     pub const CLOUD_ACCOUNT_ID: &'static str = "cloud.account.id";
     pub const CLOUD_AVAILABILITY_ZONE: &'static str = "cloud.availability_zone";
+    pub const CLOUD_BUILD_ID: &'static str = "gcp.cloud_build.build.id";
     pub const CLOUD_PLATFORM_KEY: &'static str = "cloud.platform";
     pub const CLOUD_PROVIDER: &'static str = "cloud.provider";
     pub const CLOUD_REGION: &'static str = "cloud.region";
+    pub const CONTAINER_ID: &'static str = "container.id";
+    pub const DATAFLOW_JOB_ID: &'static str = "gcp.dataflow.job.id";
+    pub const DATAFLOW_JOB_NAME: &'static str = "gcp.dataflow.job.name";
+    pub const DATAFLOW_WORKER_POOL: &'static str = "gcp.dataflow.worker_pool";
+    pub const DATAPROC_CLUSTER_NAME: &'static str = "gcp.dataproc.cluster.name";
+    pub const DATAPROC_CLUSTER_UUID: &'static str = "gcp.dataproc.cluster.uuid";
+    pub const DATAPROC_ROLE: &'static str = "gcp.dataproc.role";
+    pub const DEPLOYMENT_ENVIRONMENT: &'static str = "deployment.environment";
+    pub const GCE_ACCELERATOR_COUNT: &'static str = "gcp.gce.accelerator.count";
+    pub const GCE_ACCELERATOR_TYPE: &'static str = "gcp.gce.accelerator.type";
+    pub const GCE_IMAGE_PROJECT: &'static str = "gcp.gce.image.project";
+    pub const GCE_INSTANCE_HOSTNAME: &'static str = "gcp.gce.instance.hostname";
+    pub const GCE_INSTANCE_NAME: &'static str = "gcp.gce.instance.name";
+    pub const GCE_INSTANCE_GROUP_MANAGER_NAME: &'static str = "gcp.gce.instance_group_manager.name";
+    pub const GCE_INSTANCE_GROUP_MANAGER_REGION: &'static str = "gcp.gce.instance_group_manager.region";
+    pub const GCE_INSTANCE_GROUP_MANAGER_ZONE: &'static str = "gcp.gce.instance_group_manager.zone";
+    pub const GCE_NETWORK: &'static str = "gcp.gce.network";
+    pub const GCE_SCHEDULING_ON_HOST_MAINTENANCE: &'static str = "gcp.gce.instance.scheduling.on_host_maintenance";
+    pub const GCE_SCHEDULING_PREEMPTIBLE: &'static str = "gcp.gce.instance.scheduling.preemptible";
+    pub const GCE_SCHEDULING_PROVISIONING_MODEL: &'static str = "gcp.gce.instance.scheduling.provisioning_model";
+    pub const GCE_SUBNETWORK: &'static str = "gcp.gce.subnetwork";
     pub const GCP_COMPUTE_ENGINE: &'static str = "gcp_compute_engine";
+    pub const GCP_FLEET_MEMBERSHIP_NAME: &'static str = "gcp.fleet.membership.name";
     pub const GCP_KUBERNETES_ENGINE: &'static str = "gcp_kubernetes_engine";
+    pub const GCP_PROJECT_NUMBER: &'static str = "gcp.project.number";
+    pub const GCP_RESOURCE_TYPE: &'static str = "gcp.resource_type";
+    pub const GCP_SERVICE_ACCOUNT_EMAIL: &'static str = "gcp.service_account.email";
+    pub const GCP_SIMULATED: &'static str = "gcp.simulated";
+    pub const GCP_VERTEX_AI: &'static str = "gcp_vertex_ai";
     pub const HOST_ID: &'static str = "host.id";
+    pub const HOST_IMAGE_ID: &'static str = "host.image.id";
+    pub const HOST_IMAGE_NAME: &'static str = "host.image.name";
+    pub const HOST_IP: &'static str = "host.ip";
     pub const HOST_NAME: &'static str = "host.name";
     pub const HOST_TYPE: &'static str = "host.type";
     pub const K8S_CLUSTER_NAME: &'static str = "k8s.cluster.name";
     pub const K8S_CONTAINER_NAME: &'static str = "k8s.container.name";
+    pub const K8S_CRONJOB_NAME: &'static str = "k8s.cronjob.name";
+    pub const K8S_DAEMONSET_NAME: &'static str = "k8s.daemonset.name";
+    pub const K8S_DEPLOYMENT_NAME: &'static str = "k8s.deployment.name";
+    pub const K8S_JOB_NAME: &'static str = "k8s.job.name";
     pub const K8S_NAMESPACE_NAME: &'static str = "k8s.namespace.name";
     pub const K8S_NODE_NAME: &'static str = "k8s.node.name";
     pub const K8S_POD_NAME: &'static str = "k8s.pod.name";
+    pub const K8S_REPLICASET_NAME: &'static str = "k8s.replicaset.name";
+    pub const K8S_STATEFULSET_NAME: &'static str = "k8s.statefulset.name";
     pub const SERVICE_INSTANCE_ID: &'static str = "service.instance.id";
     pub const SERVICE_NAME: &'static str = "service.name";
     pub const SERVICE_NAMESPACE: &'static str = "service.namespace";
+    pub const SERVICE_VERSION: &'static str = "service.version";
+    pub const TPU_ACCELERATOR_TYPE: &'static str = "gcp.tpu.accelerator_type";
+    pub const TPU_TOPOLOGY: &'static str = "gcp.tpu.topology";
+    pub const TPU_WORKER_COUNT: &'static str = "gcp.tpu.worker.count";
+    pub const TPU_WORKER_ID: &'static str = "gcp.tpu.worker.id";
+    pub const VERTEX_AI_DEPLOYED_MODEL_ID: &'static str = "gcp.vertex_ai.deployed_model.id";
+    pub const VERTEX_AI_ENDPOINT_ID: &'static str = "gcp.vertex_ai.endpoint.id";
+    pub const VERTEX_AI_JOB_ID: &'static str = "gcp.vertex_ai.job.id";
+    pub const VERTEX_AI_REPLICA_INDEX: &'static str = "gcp.vertex_ai.replica.index";
+    pub const VERTEX_AI_REPLICA_TYPE: &'static str = "gcp.vertex_ai.replica.type";
     pub const FAAS_INSTANCE: &'static str = "faas.instance";
     pub const FAAS_NAME: &'static str = "faas.name";
 }
@@ -45,5 +96,7 @@ pub const NODE_NAME: &str = "node_name";
 pub const POD_NAME: &str = "pod_name";
 pub const REGION: &str = "region";
 pub const TASK_ID: &str = "task_id";
+pub const VERTEX_AI_CUSTOM_JOB: &str = "vertex_ai_custom_job";
+pub const VERTEX_AI_ENDPOINT: &str = "vertex_ai_endpoint";
 pub const ZONE: &str = "zone";
 pub const UNKNOWN_SERVICE_PREFIX: &str = "unknown_service";
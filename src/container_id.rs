@@ -0,0 +1,170 @@
+use std::{fs, path::Path};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// cgroup paths end with the container id, optionally wrapped by the runtime,
+// e.g. `/kubepods/burstable/pod<uid>/<id>` or `/system.slice/cri-containerd-<id>.scope`
+static CGROUP_CONTAINER_ID_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?P<id>[0-9a-f]{64})(?:\.scope)?$").unwrap()
+});
+// Files of the container mounted by the runtime, e.g. `/var/lib/docker/containers/<id>/hostname` (Docker)
+// or `/var/lib/containerd/io.containerd.grpc.v1.cri/containers/<id>/volumes/<volume>` (containerd CRI).
+// containerd mounts `/etc/hostname` and `/etc/resolv.conf` from `io.containerd.grpc.v1.cri/sandboxes/<id>/`,
+// the id there is the one of the pod sandbox, not of the container.
+static MOUNTINFO_CONTAINER_ID_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"/(?:docker|io\.containerd\.grpc\.v1\.cri)/containers/(?P<id>[0-9a-f]{64})/").unwrap()
+});
+
+/// Parse the container id from `/proc/self/cgroup`.
+///
+/// Works for cgroup v1 and for cgroup v2 without a cgroup namespace. With a
+/// cgroup namespace (the default on cgroup v2) the path is just `/`.
+pub(crate) fn container_id_from_cgroup(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let path = line.splitn(3, ':').nth(2)?;
+        CGROUP_CONTAINER_ID_RE.captures(path.trim()).map(|captures| captures["id"].to_string())
+    })
+}
+
+/// Parse the container id from `/proc/self/mountinfo`, looking at the root of each mount
+/// (the path of the mounted file on the host).
+///
+/// containerd only mounts per-container paths for image volumes, so on GKE with cgroup v2
+/// and on sandboxed platforms like Cloud Run the id usually can't be found.
+pub(crate) fn container_id_from_mountinfo(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let root = line.split(' ').nth(3)?;
+        MOUNTINFO_CONTAINER_ID_RE.captures(root).map(|captures| captures["id"].to_string())
+    })
+}
+
+/// Container id of the current process, read from the proc filesystem under `root`
+pub(crate) fn get_container_id(root: &Path) -> Option<String> {
+    let proc_self = root.join("proc").join("self");
+    if let Ok(cgroup) = fs::read_to_string(proc_self.join("cgroup")) {
+        if let Some(container_id) = container_id_from_cgroup(&cgroup) {
+            return Some(container_id);
+        }
+    }
+    if let Ok(mountinfo) = fs::read_to_string(proc_self.join("mountinfo")) {
+        return container_id_from_mountinfo(&mountinfo);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::FixtureDir;
+
+    const ID: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+    const SANDBOX_ID: &str = "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210";
+
+    fn fixture_root(name: &str, cgroup: &str, mountinfo: Option<&str>) -> FixtureDir {
+        let root = FixtureDir::new(&format!("container_id_{}", name));
+        root.write("proc/self/cgroup", cgroup);
+        if let Some(mountinfo) = mountinfo {
+            root.write("proc/self/mountinfo", mountinfo);
+        }
+        root
+    }
+
+    /// `/proc/self/mountinfo` of a GKE pod (containerd, cgroup v2), with an image volume if `volume_id` is set
+    fn gke_mountinfo(volume_id: Option<&str>) -> String {
+        let mut mountinfo = format!(
+            "1455 1372 0:262 / / rw,relatime master:541 - overlay overlay rw,lowerdir=/var/lib/containerd/io.containerd.snapshotter.v1.overlayfs/snapshots/2071/fs,upperdir=/var/lib/containerd/io.containerd.snapshotter.v1.overlayfs/snapshots/2079/fs\n\
+             1456 1455 0:265 / /proc rw,nosuid,nodev,noexec,relatime - proc proc rw\n\
+             1466 1455 8:1 /var/lib/kubelet/pods/6f5d0c1e-8a2b-4c3d-9e4f-0a1b2c3d4e5f/etc-hosts /etc/hosts rw,nosuid,nodev,relatime - ext4 /dev/sda1 rw\n\
+             1467 1455 8:1 /var/lib/kubelet/pods/6f5d0c1e-8a2b-4c3d-9e4f-0a1b2c3d4e5f/containers/app/4b2a1c0d /dev/termination-log rw,nosuid,nodev,relatime - ext4 /dev/sda1 rw\n\
+             1468 1455 8:1 /var/lib/containerd/io.containerd.grpc.v1.cri/sandboxes/{sandbox}/hostname /etc/hostname rw,nosuid,nodev,relatime - ext4 /dev/sda1 rw\n\
+             1469 1455 8:1 /var/lib/containerd/io.containerd.grpc.v1.cri/sandboxes/{sandbox}/resolv.conf /etc/resolv.conf rw,nosuid,nodev,relatime - ext4 /dev/sda1 rw\n",
+            sandbox = SANDBOX_ID
+        );
+        if let Some(volume_id) = volume_id {
+            mountinfo.push_str(&format!(
+                "1470 1455 8:1 /var/lib/containerd/io.containerd.grpc.v1.cri/containers/{}/volumes/9a8b7c6d /data rw,nosuid,nodev,relatime - ext4 /dev/sda1 rw\n",
+                volume_id
+            ));
+        }
+        mountinfo
+    }
+
+    #[test]
+    fn test_cgroup_v1_kubepods() {
+        let cgroup = format!(
+            "12:memory:/kubepods/burstable/pod2c48913c-b29f-11e7-9350-020968147796/{id}\n1:name=systemd:/kubepods/burstable/pod2c48913c-b29f-11e7-9350-020968147796/{id}\n",
+            id = ID
+        );
+        assert_eq!(container_id_from_cgroup(&cgroup), Some(ID.to_string()));
+    }
+
+    #[test]
+    fn test_cgroup_v1_docker() {
+        let cgroup = format!("4:cpu,cpuacct:/docker/{}\n", ID);
+        assert_eq!(container_id_from_cgroup(&cgroup), Some(ID.to_string()));
+    }
+
+    #[test]
+    fn test_cgroup_v2_systemd_scope() {
+        let cgroup = format!("0::/system.slice/cri-containerd-{}.scope\n", ID);
+        assert_eq!(container_id_from_cgroup(&cgroup), Some(ID.to_string()));
+    }
+
+    #[test]
+    fn test_cgroup_v2_namespaced() {
+        assert_eq!(container_id_from_cgroup("0::/\n"), None);
+    }
+
+    #[test]
+    fn test_mountinfo() {
+        let mountinfo = format!(
+            "736 700 0:160 / / rw,relatime - overlay overlay rw\n753 736 254:1 /var/lib/docker/containers/{}/hostname /etc/hostname rw,relatime - ext4 /dev/vda1 rw\n",
+            ID
+        );
+        assert_eq!(container_id_from_mountinfo(&mountinfo), Some(ID.to_string()));
+    }
+
+    #[test]
+    fn test_mountinfo_gke_cgroup_v2() {
+        assert_eq!(container_id_from_mountinfo(&gke_mountinfo(Some(ID))), Some(ID.to_string()));
+    }
+
+    #[test]
+    fn test_mountinfo_gke_skips_sandbox() {
+        assert_eq!(container_id_from_mountinfo(&gke_mountinfo(None)), None);
+    }
+
+    #[test]
+    fn test_get_container_id_falls_back_to_mountinfo() {
+        let mountinfo = format!("753 736 254:1 /var/lib/docker/containers/{}/hostname /etc/hostname rw - ext4 /dev/vda1 rw\n", ID);
+        let root = fixture_root("mountinfo", "0::/\n", Some(&mountinfo));
+        assert_eq!(get_container_id(&root.path), Some(ID.to_string()));
+    }
+
+    #[test]
+    fn test_get_container_id_gke_cgroup_v2() {
+        let root = fixture_root("gke", "0::/\n", Some(&gke_mountinfo(Some(ID))));
+        assert_eq!(get_container_id(&root.path), Some(ID.to_string()));
+
+        let root = fixture_root("gke_without_volume", "0::/\n", Some(&gke_mountinfo(None)));
+        assert_eq!(get_container_id(&root.path), None);
+    }
+
+    #[test]
+    fn test_get_container_id_cloud_run() {
+        // gVisor sandbox, neither the cgroup nor the mounts name the container
+        let mountinfo = "1 0 0:2 / / rw,noatime - 9p none rw,trans=fd,rfdno=4,wfdno=4,cache=remote_revalidating\n\
+                         2 1 0:5 / /dev rw,nosuid,noexec - tmpfs none rw,mode=0755\n\
+                         3 1 0:3 / /proc rw,nosuid,nodev,noexec - proc none rw\n\
+                         4 1 0:7 / /etc/hostname rw,noatime - 9p none rw,trans=fd,rfdno=6,wfdno=6\n";
+        let root = fixture_root("cloud_run", "0::/\n", Some(mountinfo));
+        assert_eq!(get_container_id(&root.path), None);
+    }
+
+    #[test]
+    fn test_get_container_id_missing_files() {
+        let root = std::env::temp_dir().join("otel_gcp_container_id_missing");
+        assert_eq!(get_container_id(&root), None);
+    }
+}
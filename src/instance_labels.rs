@@ -0,0 +1,170 @@
+use opentelemetry::KeyValue;
+
+use crate::{constants::ResourceAttributes, get_val};
+
+/// Labels with a well known resource attribute, exported under both keys.
+/// When several labels map to the same key, the first one listed wins.
+const LABEL_MAPPINGS: &[(&str, &str)] = &[
+    ("environment", ResourceAttributes::DEPLOYMENT_ENVIRONMENT),
+    ("env", ResourceAttributes::DEPLOYMENT_ENVIRONMENT),
+    ("version", ResourceAttributes::SERVICE_VERSION),
+];
+
+/// Which GCE instance labels and custom metadata attributes are exported as resource attributes
+#[derive(Debug, Clone)]
+pub struct InstanceLabelsConfig {
+    /// Export all instance labels
+    pub labels: bool,
+    /// Prefix of the resource attribute key of a label
+    pub label_prefix: String,
+    /// Allowlist of `instance/attributes/*` keys to export
+    pub attributes: Vec<String>,
+    /// Prefix of the resource attribute key of a metadata attribute
+    pub attribute_prefix: String,
+    /// Also export labels like `env` under well known keys like `deployment.environment`
+    pub well_known_mappings: bool,
+}
+
+impl Default for InstanceLabelsConfig {
+    fn default() -> Self {
+        Self {
+            labels: true,
+            label_prefix: "gcp.gce.instance.labels.".to_string(),
+            attributes: vec![],
+            attribute_prefix: "gcp.gce.instance.attributes.".to_string(),
+            well_known_mappings: true,
+        }
+    }
+}
+
+impl InstanceLabelsConfig {
+    pub fn with_labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    pub fn with_label_prefix<T: ToString>(mut self, prefix: T) -> Self {
+        self.label_prefix = prefix.to_string();
+        self
+    }
+
+    pub fn with_attributes<T: ToString>(mut self, attributes: &[T]) -> Self {
+        self.attributes = attributes.iter().map(|key| key.to_string()).collect();
+        self
+    }
+
+    pub fn with_attribute_prefix<T: ToString>(mut self, prefix: T) -> Self {
+        self.attribute_prefix = prefix.to_string();
+        self
+    }
+
+    pub fn with_well_known_mappings(mut self, enabled: bool) -> Self {
+        self.well_known_mappings = enabled;
+        self
+    }
+}
+
+/// Resource finder for instance labels and allowlisted custom metadata attributes
+pub(crate) fn get_instance_labels_resources(metadata: &serde_json::Value, config: &InstanceLabelsConfig) -> Vec<KeyValue> {
+    let mut attrs = vec![];
+
+    if config.labels {
+        if let Some(serde_json::Value::Object(labels)) = get_val::get_val(metadata, &["instance", "labels"], None) {
+            for (key, value) in labels {
+                let value = if let serde_json::Value::String(value) = value {
+                    value
+                } else {
+                    continue;
+                };
+                attrs.push(KeyValue::new(format!("{}{}", config.label_prefix, key), value.clone()));
+            }
+            if config.well_known_mappings {
+                let mut mapped_keys = vec![];
+                for (label, mapped) in LABEL_MAPPINGS {
+                    if mapped_keys.contains(mapped) {
+                        continue;
+                    }
+                    if let Some(serde_json::Value::String(value)) = labels.get(*label) {
+                        attrs.push(KeyValue::new(*mapped, value.clone()));
+                        mapped_keys.push(*mapped);
+                    }
+                }
+            }
+        }
+    }
+
+    for key in &config.attributes {
+        if let Some(serde_json::Value::String(value)) = get_val::get_val(metadata, &["instance", "attributes", key], None) {
+            attrs.push(KeyValue::new(format!("{}{}", config.attribute_prefix, key), value.clone()));
+        }
+    }
+    attrs
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry_sdk::Resource;
+    use pretty_assertions_sorted::assert_eq_sorted;
+
+    use super::*;
+
+    fn metadata() -> serde_json::Value {
+        serde_json::json!({
+            "instance": {
+                "labels": {"env": "prod", "team": "payments", "cost-center": "cc-42"},
+                "attributes": {"role": "api", "startup-script": "#!/bin/bash"},
+            },
+        })
+    }
+
+    #[test]
+    fn test_default_config() {
+        let attrs = get_instance_labels_resources(&metadata(), &InstanceLabelsConfig::default());
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("deployment.environment", "prod"),
+            KeyValue::new("gcp.gce.instance.labels.cost-center", "cc-42"),
+            KeyValue::new("gcp.gce.instance.labels.env", "prod"),
+            KeyValue::new("gcp.gce.instance.labels.team", "payments"),
+        ]);
+        assert_eq_sorted!(Resource::new(attrs), res_sould_be);
+    }
+
+    #[test]
+    fn test_environment_label_wins_over_env() {
+        let metadata = serde_json::json!({
+            "instance": {"labels": {"env": "dev", "environment": "prod"}},
+        });
+        let attrs = get_instance_labels_resources(&metadata, &InstanceLabelsConfig::default());
+        assert_eq!(attrs.iter().filter(|kv| kv.key.as_str() == "deployment.environment").count(), 1);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("deployment.environment", "prod"),
+            KeyValue::new("gcp.gce.instance.labels.env", "dev"),
+            KeyValue::new("gcp.gce.instance.labels.environment", "prod"),
+        ]);
+        assert_eq_sorted!(Resource::new(attrs), res_sould_be);
+    }
+
+    #[test]
+    fn test_allowlisted_attributes_and_prefixes() {
+        let config = InstanceLabelsConfig::default()
+            .with_label_prefix("")
+            .with_well_known_mappings(false)
+            .with_attributes(&["role", "missing"])
+            .with_attribute_prefix("vm.");
+        let attrs = get_instance_labels_resources(&metadata(), &config);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cost-center", "cc-42"),
+            KeyValue::new("env", "prod"),
+            KeyValue::new("team", "payments"),
+            KeyValue::new("vm.role", "api"),
+        ]);
+        assert_eq_sorted!(Resource::new(attrs), res_sould_be);
+    }
+
+    #[test]
+    fn test_attributes_only() {
+        let config = InstanceLabelsConfig::default().with_labels(false).with_attributes(&["role"]);
+        let attrs = get_instance_labels_resources(&metadata(), &config);
+        assert_eq_sorted!(Resource::new(attrs), Resource::new(vec![KeyValue::new("gcp.gce.instance.attributes.role", "api")]));
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use opentelemetry::KeyValue;
+use regex::Regex;
+use tracing::info;
+
+use crate::{constants::ResourceAttributes, error::OpenTelemetryError, get_val};
+
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Alphabet used by kubernetes for generated name suffixes (no vowels and no 0, 1, 3)
+static CRONJOB_POD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<cronjob>[a-z0-9.-]+)-(?P<schedule>\d{8,10})-[bcdfghjklmnpqrstvwxz2456789]{5}$").unwrap()
+});
+static DEPLOYMENT_POD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<deployment>[a-z0-9.-]+)-(?P<hash>[bcdfghjklmnpqrstvwxz2456789]{6,10})-[bcdfghjklmnpqrstvwxz2456789]{5}$").unwrap()
+});
+static STATEFULSET_POD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<statefulset>[a-z0-9.-]+)-\d+$").unwrap()
+});
+
+/// Connection settings for the Kubernetes API used to resolve pod owner references
+#[derive(Debug, Clone)]
+pub struct K8sApiConfig {
+    /// Base url of the API server, e.g. `https://10.0.0.1:443`
+    pub api_url: String,
+    /// File with the bearer token sent in the `Authorization` header
+    pub token_path: Option<PathBuf>,
+    /// PEM file with the CA certificate of the API server
+    pub ca_cert_path: Option<PathBuf>,
+    /// Time allowed for all the requests resolving the workload of the pod
+    pub timeout: Duration,
+}
+
+impl K8sApiConfig {
+    pub fn new<T>(api_url: T) -> Self
+    where
+        T: ToString,
+    {
+        Self {
+            api_url: api_url.to_string(),
+            token_path: None,
+            ca_cert_path: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Config for the API server of the current cluster, authenticated with the
+    /// service account token mounted into the pod.
+    pub fn in_cluster() -> Self {
+        let host = env::var("KUBERNETES_SERVICE_HOST").unwrap_or_default();
+        let port = env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".to_string());
+        let host = if host.contains(':') {
+            format!("[{}]", host)
+        } else {
+            host
+        };
+        let dir = PathBuf::from(SERVICE_ACCOUNT_DIR);
+        Self::new(format!("https://{}:{}", host, port))
+            .with_token_path(dir.join("token"))
+            .with_ca_cert_path(dir.join("ca.crt"))
+    }
+
+    pub fn with_token_path<T: Into<PathBuf>>(mut self, token_path: T) -> Self {
+        self.token_path = Some(token_path.into());
+        self
+    }
+
+    pub fn with_ca_cert_path<T: Into<PathBuf>>(mut self, ca_cert_path: T) -> Self {
+        self.ca_cert_path = Some(ca_cert_path.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Owner {
+    kind: String,
+    name: String,
+}
+
+struct K8sApiClient<'a> {
+    config: &'a K8sApiConfig,
+    client: reqwest::Client,
+    token: Option<String>,
+    deadline: Instant,
+}
+
+impl<'a> K8sApiClient<'a> {
+    fn new(config: &'a K8sApiConfig) -> Result<Self, OpenTelemetryError> {
+        let deadline = Instant::now() + config.timeout;
+        let mut builder = reqwest::Client::builder();
+        if let Some(ca_cert_path) = &config.ca_cert_path {
+            if let Ok(pem) = fs::read(ca_cert_path) {
+                let cert = reqwest::Certificate::from_pem(&pem).map_err(OpenTelemetryError::new)?;
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        let client = builder.build().map_err(OpenTelemetryError::new)?;
+        let token = config
+            .token_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|token| token.trim().to_string());
+        Ok(Self { config, client, token, deadline })
+    }
+
+    /// Returns the controller owner of the object at `path`, or its first owner if none is marked as controller
+    async fn get_owner(&self, path: &str) -> Result<Option<Owner>, OpenTelemetryError> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            Err(OpenTelemetryError::new(format!("timed out before requesting {}", path)))?
+        }
+        let url = format!("{}{}", self.config.api_url.trim_end_matches('/'), path);
+        let mut request = self.client.get(url).timeout(remaining);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(OpenTelemetryError::new)?;
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            Err(OpenTelemetryError::new(format!("access denied to {}", path)))?
+        }
+        if !status.is_success() {
+            Err(OpenTelemetryError::new(format!("unexpected status {} for {}", status, path)))?
+        }
+        let body = response.json::<serde_json::Value>().await.map_err(OpenTelemetryError::new)?;
+
+        let owners = if let Some(serde_json::Value::Array(owners)) = get_val::get_val(&body, &["metadata", "ownerReferences"], None) {
+            owners
+        } else {
+            return Ok(None);
+        };
+        let owner = owners
+            .iter()
+            .find(|owner| owner.get("controller") == Some(&serde_json::Value::Bool(true)))
+            .or_else(|| owners.first());
+        let owner = owner.and_then(|owner| {
+            match (owner.get("kind"), owner.get("name")) {
+                (Some(serde_json::Value::String(kind)), Some(serde_json::Value::String(name))) => Some(Owner {
+                    kind: kind.clone(),
+                    name: name.clone(),
+                }),
+                _ => None,
+            }
+        });
+        Ok(owner)
+    }
+}
+
+/// Resolve the workload owning the pod by walking owner references through the Kubernetes API
+async fn get_workload_from_api(config: &K8sApiConfig, namespace: &str, pod_name: &str) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let api = K8sApiClient::new(config)?;
+    let mut attrs = vec![];
+
+    let owner = api.get_owner(&format!("/api/v1/namespaces/{}/pods/{}", namespace, pod_name)).await?;
+    let owner = if let Some(owner) = owner {
+        owner
+    } else {
+        return Ok(attrs);
+    };
+    match owner.kind.as_str() {
+        "ReplicaSet" => {
+            attrs.push(KeyValue::new(ResourceAttributes::K8S_REPLICASET_NAME, owner.name.clone()));
+            let path = format!("/apis/apps/v1/namespaces/{}/replicasets/{}", namespace, owner.name);
+            match api.get_owner(&path).await {
+                Ok(Some(Owner { kind, name })) if kind == "Deployment" => {
+                    attrs.push(KeyValue::new(ResourceAttributes::K8S_DEPLOYMENT_NAME, name));
+                }
+                Ok(_) => {}
+                // The service account may read pods but not ReplicaSets, keep the owner found so far
+                Err(e) => info!("Failed to get the owner of replicaset {}: {:?}", owner.name, e),
+            }
+        }
+        "StatefulSet" => {
+            attrs.push(KeyValue::new(ResourceAttributes::K8S_STATEFULSET_NAME, owner.name));
+        }
+        "DaemonSet" => {
+            attrs.push(KeyValue::new(ResourceAttributes::K8S_DAEMONSET_NAME, owner.name));
+        }
+        "Job" => {
+            attrs.push(KeyValue::new(ResourceAttributes::K8S_JOB_NAME, owner.name.clone()));
+            let path = format!("/apis/batch/v1/namespaces/{}/jobs/{}", namespace, owner.name);
+            match api.get_owner(&path).await {
+                Ok(Some(Owner { kind, name })) if kind == "CronJob" => {
+                    attrs.push(KeyValue::new(ResourceAttributes::K8S_CRONJOB_NAME, name));
+                }
+                Ok(_) => {}
+                Err(e) => info!("Failed to get the owner of job {}: {:?}", owner.name, e),
+            }
+        }
+        _ => {}
+    }
+    Ok(attrs)
+}
+
+/// Guess the workload from the naming scheme kubernetes uses for generated pods.
+///
+/// Pods of DaemonSets and Jobs share the same `<name>-<suffix>` scheme and can't be
+/// told apart, so they are not reported.
+pub(crate) fn get_workload_from_pod_name(pod_name: &str) -> Vec<KeyValue> {
+    if let Some(captures) = CRONJOB_POD_RE.captures(pod_name) {
+        let cronjob = &captures["cronjob"];
+        return vec![
+            KeyValue::new(ResourceAttributes::K8S_CRONJOB_NAME, cronjob.to_string()),
+            KeyValue::new(ResourceAttributes::K8S_JOB_NAME, format!("{}-{}", cronjob, &captures["schedule"])),
+        ];
+    }
+    if let Some(captures) = DEPLOYMENT_POD_RE.captures(pod_name) {
+        let deployment = &captures["deployment"];
+        return vec![
+            KeyValue::new(ResourceAttributes::K8S_DEPLOYMENT_NAME, deployment.to_string()),
+            KeyValue::new(ResourceAttributes::K8S_REPLICASET_NAME, format!("{}-{}", deployment, &captures["hash"])),
+        ];
+    }
+    if let Some(captures) = STATEFULSET_POD_RE.captures(pod_name) {
+        return vec![KeyValue::new(ResourceAttributes::K8S_STATEFULSET_NAME, captures["statefulset"].to_string())];
+    }
+    vec![]
+}
+
+/// Resource finder for the workload (Deployment, StatefulSet, Job, ...) owning the pod.
+///
+/// Falls back to pod name heuristics when the Kubernetes API can't be used,
+/// e.g. when the service account isn't allowed to read pods.
+pub(crate) async fn get_workload_resources(config: &K8sApiConfig, namespace: &str, pod_name: &str) -> Vec<KeyValue> {
+    if pod_name.is_empty() {
+        return vec![];
+    }
+    if !namespace.is_empty() {
+        match get_workload_from_api(config, namespace, pod_name).await {
+            Ok(attrs) => return attrs,
+            Err(e) => info!("Failed to get workload from kubernetes api, using pod name: {:?}", e),
+        }
+    }
+    get_workload_from_pod_name(pod_name)
+}
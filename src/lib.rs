@@ -6,27 +6,47 @@ mod test_envs;
 mod test_detector;
 #[cfg(test)]
 mod test_mapping;
+#[cfg(test)]
+mod test_k8s_workload;
+#[cfg(test)]
+mod test_fixtures;
 
 use core::str;
-use std::{env, fs::File, io::Read, sync::Arc, time::Duration};
+use std::{env, fs::{self, File}, io::Read, path::{Path, PathBuf}, sync::Arc, time::{Duration, Instant}};
 
+use constants::ResourceAttributes;
 use error::OpenTelemetryError;
 use opentelemetry::KeyValue;
 use opentelemetry_sdk::{resource::ResourceDetector, Resource};
 use regex::Regex;
 use serde::de::value::Error;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 pub mod error;
 pub mod mapping;
 pub mod constants;
+pub mod k8s_workload;
+pub mod instance_labels;
+mod container_id;
+mod local_project;
+mod otel_metadata;
+pub mod platform;
+pub mod report;
+pub mod simulation;
+
+use instance_labels::InstanceLabelsConfig;
+use k8s_workload::K8sApiConfig;
+use platform::{DetectionContext, PlatformDetector};
+use report::DetectionReport;
+use simulation::SimulatedPlatform;
 
 struct Zone {
+    project_number: String,
     region: String,
     zone: String,
 }
 
 fn parse_zone(text: &str) -> Zone {
-    let zone_region_re = Regex::new(r"projects/\d+/zones/(?P<zone>(?P<region>\w+-\w+)-\w+)").unwrap();
+    let zone_region_re = Regex::new(r"projects/(?P<project_number>\d+)/zones/(?P<zone>(?P<region>\w+-\w+)-\w+)").unwrap();
 
     // Example usage
     // let text = "projects/123456/zones/us-central1-a";
@@ -34,6 +54,7 @@ fn parse_zone(text: &str) -> Zone {
         if let Some(zone) = captures.name("zone") {
             if let Some(region) = captures.name("region") {
                 return Zone {
+                    project_number: captures.name("project_number").map(|n| n.as_str().to_string()).unwrap_or_default(),
                     region: region.as_str().to_string(),
                     zone: zone.as_str().to_string(),
                 };
@@ -42,11 +63,48 @@ fn parse_zone(text: &str) -> Zone {
         }
     }
     Zone {
+        project_number: "".to_string(),
         region: "".to_string(),
         zone: "".to_string(),
     }
 }
 
+struct InstanceGroupManager {
+    name: String,
+    zone: Option<String>,
+    region: Option<String>,
+}
+
+/// Parse the `created-by` instance attribute of instances managed by an instance group manager
+fn parse_instance_group_manager(text: &str) -> Option<InstanceGroupManager> {
+    let igm_re = Regex::new(r"^projects/[^/]+/(?:zones/(?P<zone>[\w-]+)|regions/(?P<region>[\w-]+))/instanceGroupManagers/(?P<name>[\w-]+)$").unwrap();
+
+    // Example usage
+    // let text = "projects/123456/zones/us-central1-a/instanceGroupManagers/my-mig";
+    // let text = "projects/123456/regions/us-central1/instanceGroupManagers/my-mig";
+    let captures = igm_re.captures(text)?;
+    Some(InstanceGroupManager {
+        name: captures.name("name")?.as_str().to_string(),
+        zone: captures.name("zone").map(|zone| zone.as_str().to_string()),
+        region: captures.name("region").map(|region| region.as_str().to_string()),
+    })
+}
+
+struct Image {
+    project: String,
+    name: String,
+}
+
+/// Parse an image reference like `projects/debian-cloud/global/images/debian-12-bookworm-v20240110`
+fn parse_image(text: &str) -> Option<Image> {
+    let image_re = Regex::new(r"^projects/(?P<project>[^/]+)/global/images/(?P<name>[^/]+)$").unwrap();
+    let captures = image_re.captures(text)?;
+    Some(Image {
+        project: captures.name("project")?.as_str().to_string(),
+        name: captures.name("name")?.as_str().to_string(),
+    })
+}
+
 async fn get_metadata() ->  Result<serde_json::Value, OpenTelemetryError> {
     let client = reqwest::Client::builder().build().map_err(OpenTelemetryError::new)?;
 
@@ -62,73 +120,584 @@ async fn get_metadata() ->  Result<serde_json::Value, OpenTelemetryError> {
 }
 
 
-fn get_metadata_resources(metadata: &serde_json::Value) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+/// Resource finder for the project of the metadata server.
+///
+/// `gcp.project.number` comes from `project/numericProjectId`, which wins over the project
+/// number of the `instance/zone` path when they differ; the mismatch is only logged.
+fn get_metadata_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
      let project_id = if let Some(serde_json::Value::String(project_id)) = get_val::get_val(metadata, &["project", "projectId"], None) {
         project_id
      } else {
-        Err(OpenTelemetryError::new("project id not found"))?
+        Err(OpenTelemetryError::new("project.projectId not found"))?
      };
-     let zone = if let Some(serde_json::Value::String(zone)) = get_val::get_val(metadata, &["instance", "zone"], None) {
-        zone
-     } else {
-        Err(OpenTelemetryError::new("zone not found"))?
-     };
-    let attrs = vec![
+    if get_val::get_val(metadata, &["instance", "zone"], None).is_none() {
+        context.missing("instance.zone")?;
+    }
+    let mut attrs = vec![
         KeyValue::new("cloud.account.id", project_id.clone()),
         KeyValue::new("cloud.provider", "gcp"),
     ];
+
+    let project_number = match get_val::get_val(metadata, &["project", "numericProjectId"], None) {
+        Some(serde_json::Value::Number(project_number)) => Some(project_number.to_string()),
+        Some(serde_json::Value::String(project_number)) => Some(project_number.clone()),
+        _ => None,
+    };
+    if let Some(project_number) = project_number {
+        let zone = if let Some(serde_json::Value::String(zone)) = get_val::get_val(metadata, &["instance", "zone"], None) {
+            zone.as_str()
+        } else {
+            ""
+        };
+        let zone_project_number = parse_zone(zone).project_number;
+        if !zone_project_number.is_empty() && zone_project_number != project_number {
+            warn!(
+                "Project number {} does not match the project number {} of zone {}",
+                project_number, zone_project_number, zone
+            );
+        }
+        attrs.push(KeyValue::new(ResourceAttributes::GCP_PROJECT_NUMBER, project_number));
+    }
     Ok(attrs)
 }
 
 
+/// [`get_metadata_resources`], in best-effort mode only `cloud.provider` is returned when the project is missing
+fn get_project_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    if get_val::get_val(context.metadata, &["project", "projectId"], None).is_none() {
+        context.missing("project.projectId")?;
+        return Ok(vec![KeyValue::new("cloud.provider", "gcp")]);
+    }
+    get_metadata_resources(context)
+}
+
+
+/// Resource finder for `cloud.availability_zone` and `cloud.region` from the instance zone
+fn get_zone_resources(metadata: &serde_json::Value) -> Vec<KeyValue> {
+    if let Some(serde_json::Value::String(zone)) = get_val::get_val(metadata, &["instance", "zone"], None) {
+        let zone_and_region = parse_zone(zone);
+        if !zone_and_region.region.is_empty() {
+            return vec![
+                KeyValue::new("cloud.availability_zone", zone_and_region.zone),
+                KeyValue::new("cloud.region", zone_and_region.region),
+            ];
+        }
+    }
+    vec![]
+}
+
+
+/// Resource finder for the email of the default service account the workload runs as
+fn get_service_account_resources(metadata: &serde_json::Value) -> Vec<KeyValue> {
+    if let Some(serde_json::Value::String(email)) = get_val::get_val(metadata, &["instance", "service-accounts", "default", "email"], None) {
+        vec![KeyValue::new(ResourceAttributes::GCP_SERVICE_ACCOUNT_EMAIL, email.clone())]
+    } else {
+        vec![]
+    }
+}
+
+
+/// Resource finder for the scheduling options (Spot, preemptible, maintenance policy) of the VM
+fn get_scheduling_resources(metadata: &serde_json::Value) -> Vec<KeyValue> {
+    let mut attrs = vec![];
+    if let Some(serde_json::Value::String(preemptible)) = get_val::get_val(metadata, &["instance", "scheduling", "preemptible"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::GCE_SCHEDULING_PREEMPTIBLE, preemptible.eq_ignore_ascii_case("true")));
+    }
+    if let Some(serde_json::Value::String(provisioning_model)) = get_val::get_val(metadata, &["instance", "scheduling", "provisioningModel"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::GCE_SCHEDULING_PROVISIONING_MODEL, provisioning_model.clone()));
+    }
+    if let Some(serde_json::Value::String(on_host_maintenance)) = get_val::get_val(metadata, &["instance", "scheduling", "onHostMaintenance"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::GCE_SCHEDULING_ON_HOST_MAINTENANCE, on_host_maintenance.clone()));
+    }
+    attrs
+}
+
+
+/// GPUs built into accelerator-optimized machine types, keyed by machine type prefix.
+/// Only a fallback for VMs without the NVIDIA driver, new machine families have to be added here.
+const GPU_MACHINE_TYPES: &[(&str, &str)] = &[
+    ("a2-highgpu-", "nvidia-tesla-a100"),
+    ("a2-megagpu-", "nvidia-tesla-a100"),
+    ("a2-ultragpu-", "nvidia-a100-80gb"),
+    ("a3-highgpu-", "nvidia-h100-80gb"),
+    ("a3-edgegpu-", "nvidia-h100-80gb"),
+    ("a3-megagpu-", "nvidia-h100-mega-80gb"),
+    ("a3-ultragpu-", "nvidia-h200-141gb"),
+    ("g2-standard-", "nvidia-l4"),
+];
+
+/// Resource finder for the GPUs of accelerator-optimized machine types (A2, A3, G2),
+/// guessed from [`GPU_MACHINE_TYPES`] since the metadata server doesn't list the GPUs of a VM.
+///
+/// [`get_nvidia_gpu_resources`] replaces them when the driver is installed.
+fn get_gpu_resources(machine_type: &str) -> Vec<KeyValue> {
+    let machine_type = machine_type.rsplit('/').next().unwrap_or_default();
+    let (prefix, accelerator_type) = if let Some(gpu) = GPU_MACHINE_TYPES.iter().find(|(prefix, _)| machine_type.starts_with(prefix)) {
+        gpu
+    } else {
+        return vec![];
+    };
+    let size = &machine_type[prefix.len()..];
+    let count = if let Some(count) = size.strip_suffix('g') {
+        // e.g. a2-highgpu-4g
+        count.parse::<i64>().ok()
+    } else {
+        // G2 machine types are sized by vCPUs, e.g. g2-standard-24 has 2 GPUs
+        match size {
+            "4" | "8" | "12" | "16" | "32" => Some(1),
+            "24" => Some(2),
+            "48" => Some(4),
+            "96" => Some(8),
+            _ => None,
+        }
+    };
+    let count = if let Some(count) = count {
+        count
+    } else {
+        return vec![];
+    };
+    vec![
+        KeyValue::new(ResourceAttributes::GCE_ACCELERATOR_TYPE, accelerator_type.to_string()),
+        KeyValue::new(ResourceAttributes::GCE_ACCELERATOR_COUNT, count),
+    ]
+}
+
+
+/// GCE accelerator type of a GPU model reported by the NVIDIA driver, e.g. `Tesla T4` is `nvidia-tesla-t4`
+fn accelerator_type_from_model(model: &str) -> String {
+    let model = model.trim().to_lowercase();
+    let model = model.strip_prefix("nvidia ").unwrap_or(&model);
+    format!("nvidia-{}", model.split_whitespace().collect::<Vec<_>>().join("-"))
+}
+
+/// Resource finder for the NVIDIA GPUs attached to the VM, whatever its machine type (e.g. N1 with a T4),
+/// read from `/proc/driver/nvidia/gpus/<pci address>/information` under `root`.
+/// Nothing is found when the driver isn't installed.
+fn get_nvidia_gpu_resources(root: &Path) -> Vec<KeyValue> {
+    let gpus = if let Ok(gpus) = fs::read_dir(root.join("proc/driver/nvidia/gpus")) {
+        gpus
+    } else {
+        return vec![];
+    };
+    let mut count = 0;
+    let mut model = None;
+    for gpu in gpus.flatten() {
+        let information = if let Ok(information) = fs::read_to_string(gpu.path().join("information")) {
+            information
+        } else {
+            continue;
+        };
+        count += 1;
+        if model.is_none() {
+            model = information
+                .lines()
+                .find_map(|line| line.strip_prefix("Model:"))
+                .map(accelerator_type_from_model);
+        }
+    }
+    match model {
+        Some(model) => vec![
+            KeyValue::new(ResourceAttributes::GCE_ACCELERATOR_TYPE, model),
+            KeyValue::new(ResourceAttributes::GCE_ACCELERATOR_COUNT, count as i64),
+        ],
+        None => vec![],
+    }
+}
+
+
+/// Resource finder for the IPs and VPC network of the VM network interfaces.
+/// The network of the first interface is reported, shortened to its name.
+fn get_network_resources(metadata: &serde_json::Value, external_ip: bool) -> Vec<KeyValue> {
+    let interfaces = if let Some(serde_json::Value::Array(interfaces)) = get_val::get_val(metadata, &["instance", "networkInterfaces"], None) {
+        interfaces
+    } else {
+        return vec![];
+    };
+
+    let mut attrs = vec![];
+    let mut ips: Vec<opentelemetry::StringValue> = vec![];
+    for interface in interfaces {
+        if let Some(serde_json::Value::String(ip)) = get_val::get_val(interface, &["ip"], None) {
+            ips.push(ip.clone().into());
+        }
+        if external_ip {
+            if let Some(serde_json::Value::Array(access_configs)) = get_val::get_val(interface, &["accessConfigs"], None) {
+                for access_config in access_configs {
+                    if let Some(serde_json::Value::String(ip)) = get_val::get_val(access_config, &["externalIp"], None) {
+                        if !ip.is_empty() {
+                            ips.push(ip.clone().into());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if !ips.is_empty() {
+        attrs.push(KeyValue::new(ResourceAttributes::HOST_IP, opentelemetry::Value::Array(ips.into())));
+    }
+
+    if let Some(serde_json::Value::String(network)) = get_val::get_val(metadata, &["instance", "networkInterfaces", "0", "network"], None) {
+        if let Some(network) = network.rsplit('/').next() {
+            attrs.push(KeyValue::new(ResourceAttributes::GCE_NETWORK, network.to_string()));
+        }
+    }
+    if let Some(serde_json::Value::String(subnetwork)) = get_val::get_val(metadata, &["instance", "networkInterfaces", "0", "subnetwork"], None) {
+        if let Some(subnetwork) = subnetwork.rsplit('/').next() {
+            attrs.push(KeyValue::new(ResourceAttributes::GCE_SUBNETWORK, subnetwork.to_string()));
+        }
+    }
+    attrs
+}
+
+
 /// Resource finder for common GCE attributes
 /// 
 /// See: https://cloud.google.com/compute/docs/storing-retrieving-metadata
-fn get_gce_resources(metadata: &serde_json::Value) -> Result<Vec<KeyValue>, OpenTelemetryError> {
-
-    let mut attrs = get_metadata_resources(metadata)?;
+fn get_gce_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
     let host_id = if let Some(serde_json::Value::String(host_id)) = get_val::get_val(metadata, &["instance", "id"], None) {
         host_id.clone()
     } else {
-        Err(OpenTelemetryError::new("host id not found"))?
+        Err(OpenTelemetryError::new("instance.id not found"))?
+    };
+
+    let mut attrs = get_project_resources(context)?;
+    attrs.push(KeyValue::new("cloud.platform", "gcp_compute_engine"));
+    attrs.push(KeyValue::new("host.id", host_id));
+
+    if let Some(serde_json::Value::String(machine_type)) = get_val::get_val(metadata, &["instance", "machineType"], None) {
+        attrs.push(KeyValue::new("host.type", machine_type.clone()));
+        attrs.extend(get_gpu_resources(machine_type));
+    } else {
+        context.missing("instance.machineType")?
+    }
+    if let Some(serde_json::Value::String(zone_and_region)) = get_val::get_val(metadata, &["instance", "zone"], None) {
+        let zone_and_region = parse_zone(zone_and_region);
+        attrs.push(KeyValue::new("cloud.availability_zone", zone_and_region.zone));
+        attrs.push(KeyValue::new("cloud.region", zone_and_region.region));
+    } else {
+        context.missing("instance.zone")?
+    }
+    if let Some(serde_json::Value::String(host_name)) = get_val::get_val(metadata, &["instance", "name"], None) {
+        attrs.push(KeyValue::new("host.name", host_name.clone()));
+        attrs.push(KeyValue::new(ResourceAttributes::GCE_INSTANCE_NAME, host_name.clone()));
+    } else {
+        context.missing("instance.name")?
+    }
+
+    if let Some(serde_json::Value::String(hostname)) = get_val::get_val(metadata, &["instance", "hostname"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::GCE_INSTANCE_HOSTNAME, hostname.clone()));
+    }
+
+    if let Some(serde_json::Value::String(image)) = get_val::get_val(metadata, &["instance", "image"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::HOST_IMAGE_ID, image.clone()));
+        if let Some(image) = parse_image(image) {
+            attrs.push(KeyValue::new(ResourceAttributes::HOST_IMAGE_NAME, image.name));
+            attrs.push(KeyValue::new(ResourceAttributes::GCE_IMAGE_PROJECT, image.project));
+        }
+    }
+
+    if let Some(serde_json::Value::String(created_by)) = get_val::get_val(metadata, &["instance", "attributes", "created-by"], None) {
+        if let Some(igm) = parse_instance_group_manager(created_by) {
+            attrs.push(KeyValue::new(ResourceAttributes::GCE_INSTANCE_GROUP_MANAGER_NAME, igm.name));
+            if let Some(zone) = igm.zone {
+                attrs.push(KeyValue::new(ResourceAttributes::GCE_INSTANCE_GROUP_MANAGER_ZONE, zone));
+            }
+            if let Some(region) = igm.region {
+                attrs.push(KeyValue::new(ResourceAttributes::GCE_INSTANCE_GROUP_MANAGER_REGION, region));
+            }
+        }
+    }
+
+    attrs.extend(get_scheduling_resources(metadata));
+    Ok(attrs)
+}
+
+/// Read a value from the `tpu-env` instance attribute, which holds lines like `TOPOLOGY: '2x2x2'`
+fn get_tpu_env_value(tpu_env: &str, key: &str) -> Option<String> {
+    tpu_env.lines().find_map(|line| {
+        let (line_key, value) = line.split_once(':')?;
+        if line_key.trim() == key {
+            Some(value.trim().trim_matches('\'').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Resource finder for Cloud TPU VM attributes
+///
+/// See: https://cloud.google.com/tpu/docs/system-architecture-tpu-vm
+fn get_tpu_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    let accelerator_type = if let Some(serde_json::Value::String(accelerator_type)) = get_val::get_val(metadata, &["instance", "attributes", "accelerator-type"], None) {
+        accelerator_type.clone()
+    } else {
+        Err(OpenTelemetryError::new("instance.attributes.accelerator-type not found"))?
     };
-    let machine_type = if let Some(serde_json::Value::String(machine_type)) = get_val::get_val(metadata, &["instance", "machineType"], None) {
-        machine_type.clone()
+
+    let mut attrs = get_gce_resources(context)?;
+    attrs.push(KeyValue::new(ResourceAttributes::TPU_ACCELERATOR_TYPE, accelerator_type));
+
+    if let Some(serde_json::Value::String(worker_number)) = get_val::get_val(metadata, &["instance", "attributes", "agent-worker-number"], None) {
+        if let Ok(worker_number) = worker_number.trim().parse::<i64>() {
+            attrs.push(KeyValue::new(ResourceAttributes::TPU_WORKER_ID, worker_number));
+        }
+    }
+
+    if let Some(serde_json::Value::String(endpoints)) = get_val::get_val(metadata, &["instance", "attributes", "worker-network-endpoints"], None) {
+        let worker_count = endpoints.split(',').filter(|endpoint| !endpoint.trim().is_empty()).count();
+        if worker_count > 0 {
+            attrs.push(KeyValue::new(ResourceAttributes::TPU_WORKER_COUNT, worker_count as i64));
+        }
+    }
+
+    if let Some(serde_json::Value::String(tpu_env)) = get_val::get_val(metadata, &["instance", "attributes", "tpu-env"], None) {
+        if let Some(topology) = get_tpu_env_value(tpu_env, "TOPOLOGY") {
+            attrs.push(KeyValue::new(ResourceAttributes::TPU_TOPOLOGY, topology));
+        }
+    }
+
+    attrs.push(KeyValue::new("gcp.resource_type", "tpu_worker"));
+    Ok(attrs)
+}
+
+
+/// Resource finder for Dataproc cluster node attributes
+///
+/// See: https://cloud.google.com/dataproc/docs/concepts/configuring-clusters/metadata
+fn get_dataproc_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    let cluster_name = if let Some(serde_json::Value::String(cluster_name)) = get_val::get_val(metadata, &["instance", "attributes", "dataproc-cluster-name"], None) {
+        cluster_name.clone()
     } else {
-        Err(OpenTelemetryError::new("not gce resources"))?
+        Err(OpenTelemetryError::new("instance.attributes.dataproc-cluster-name not found"))?
     };
-    let zone_and_region = if let Some(serde_json::Value::String(zone_and_region)) = get_val::get_val(metadata, &["instance", "zone"], None) {
-        parse_zone(zone_and_region)
+
+    let mut attrs = get_gce_resources(context)?;
+    attrs.push(KeyValue::new(ResourceAttributes::DATAPROC_CLUSTER_NAME, cluster_name));
+
+    if let Some(serde_json::Value::String(cluster_uuid)) = get_val::get_val(metadata, &["instance", "attributes", "dataproc-cluster-uuid"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::DATAPROC_CLUSTER_UUID, cluster_uuid.clone()));
+    }
+    if let Some(serde_json::Value::String(role)) = get_val::get_val(metadata, &["instance", "attributes", "dataproc-role"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::DATAPROC_ROLE, role.to_lowercase()));
+    }
+
+    attrs.push(KeyValue::new("gcp.resource_type", "cloud_dataproc_cluster"));
+    Ok(attrs)
+}
+
+
+/// Resource finder for Dataflow worker attributes
+fn get_dataflow_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    let job_id = if let Some(serde_json::Value::String(job_id)) = get_val::get_val(metadata, &["instance", "attributes", "job_id"], None) {
+        job_id.clone()
     } else {
-        Err(OpenTelemetryError::new("not gce resources"))?
+        Err(OpenTelemetryError::new("instance.attributes.job_id not found"))?
     };
-    let host_name = if let Some(serde_json::Value::String(host_name)) = get_val::get_val(metadata, &["instance", "name"], None) {
-        host_name.clone()
+
+    let mut attrs = get_gce_resources(context)?;
+    attrs.push(KeyValue::new(ResourceAttributes::DATAFLOW_JOB_ID, job_id));
+
+    if let Some(serde_json::Value::String(job_name)) = get_val::get_val(metadata, &["instance", "attributes", "job_name"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::DATAFLOW_JOB_NAME, job_name.clone()));
+    }
+    if let Some(serde_json::Value::String(worker_pool)) = get_val::get_val(metadata, &["instance", "attributes", "worker_pool"], None) {
+        attrs.push(KeyValue::new(ResourceAttributes::DATAFLOW_WORKER_POOL, worker_pool.clone()));
+    }
+
+    attrs.push(KeyValue::new("gcp.resource_type", "dataflow_job"));
+    Ok(attrs)
+}
+
+
+/// Resource finder for Cloud Batch task attributes, on top of the ones of the GCE instance running the task
+///
+/// See: https://cloud.google.com/batch/docs/create-run-job#predefined-environment-variables
+fn get_batch_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    let job_id = if let Ok(job_id) = env::var("BATCH_JOB_ID") {
+        job_id
+    } else if let Ok(job_id) = env::var("BATCH_JOB_UID") {
+        job_id
     } else {
-        Err(OpenTelemetryError::new("not gce resources"))?
+        Err(OpenTelemetryError::new("BATCH_JOB_ID not found"))?
     };
-    
-    attrs.push(KeyValue::new("cloud.platform", "gcp_compute_engine"));
-    attrs.push(KeyValue::new("cloud.availability_zone", zone_and_region.zone));
-    attrs.push(KeyValue::new("cloud.region", zone_and_region.region));
-    attrs.push(KeyValue::new("host.type", machine_type));
-    attrs.push(KeyValue::new("host.id", host_id));
-    attrs.push(KeyValue::new("host.name", host_name));
+
+    let mut attrs = get_gce_resources(context)?;
+    attrs.push(KeyValue::new(ResourceAttributes::BATCH_JOB_ID, job_id));
+
+    if let Some(task_index) = env::var("BATCH_TASK_INDEX").ok().and_then(|v| v.parse::<i64>().ok()) {
+        attrs.push(KeyValue::new(ResourceAttributes::BATCH_TASK_INDEX, task_index));
+    }
+    if let Some(task_count) = env::var("BATCH_TASK_COUNT").ok().and_then(|v| v.parse::<i64>().ok()) {
+        attrs.push(KeyValue::new(ResourceAttributes::BATCH_TASK_COUNT, task_count));
+    }
+    if let Some(attempt) = env::var("BATCH_TASK_RETRY_ATTEMPT").ok().and_then(|v| v.parse::<i64>().ok()) {
+        attrs.push(KeyValue::new(ResourceAttributes::BATCH_TASK_ATTEMPT, attempt));
+    }
     Ok(attrs)
 }
 
-/// Resource finder for GKE attributes
-fn get_gke_resources(metadata: &serde_json::Value) -> Result<Vec<KeyValue>, OpenTelemetryError> {
-    if env::var("KUBERNETES_SERVICE_HOST").is_err() {
-        Err(OpenTelemetryError::new("KUBERNETES_SERVICE_HOST not found"))?
+
+/// Resource finder for Cloud Build step attributes
+///
+/// Works without metadata since the metadata server is restricted during builds,
+/// the project then comes from the `PROJECT_ID` build substitution.
+/// See: https://cloud.google.com/build/docs/configuring-builds/substitute-variable-values
+fn get_cloudbuild_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    let build_id = if let Ok(build_id) = env::var("BUILD_ID") {
+        build_id
+    } else {
+        Err(OpenTelemetryError::new("BUILD_ID not found"))?
+    };
+    if env::var("BUILDER_OUTPUT").is_err() {
+        Err(OpenTelemetryError::new("BUILDER_OUTPUT not found"))?
+    }
+
+    let mut attrs = if let Ok(attrs) = get_metadata_resources(context) {
+        attrs
+    } else if let Ok(project_id) = env::var("PROJECT_ID") {
+        vec![
+            KeyValue::new("cloud.account.id", project_id),
+            KeyValue::new("cloud.provider", "gcp"),
+        ]
+    } else {
+        Err(OpenTelemetryError::new("project.projectId and PROJECT_ID not found"))?
+    };
+
+    if !attrs.iter().any(|kv| kv.key.as_str() == ResourceAttributes::GCP_PROJECT_NUMBER) {
+        if let Ok(project_number) = env::var("PROJECT_NUMBER") {
+            attrs.push(KeyValue::new(ResourceAttributes::GCP_PROJECT_NUMBER, project_number));
+        }
+    }
+    if let Ok(location) = env::var("LOCATION") {
+        if location != "global" {
+            attrs.push(KeyValue::new("cloud.region", location));
+        }
+    }
+
+    attrs.push(KeyValue::new(ResourceAttributes::CLOUD_BUILD_ID, build_id));
+    Ok(attrs)
+}
+
+
+/// Resource finder for Vertex AI prediction and custom training containers
+///
+/// See: https://cloud.google.com/vertex-ai/docs/predictions/custom-container-requirements#aip-variables
+/// and https://cloud.google.com/vertex-ai/docs/training/distributed-training#cluster-spec-format
+///
+/// The containers run in Google-managed GKE clusters, which the metadata server describes like
+/// the user's own ones. They are reported as `gcp_vertex_ai`, with `gcp.resource_type` telling
+/// endpoints and custom jobs apart.
+fn get_vertex_ai_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    let deployed_model_id = env::var("AIP_DEPLOYED_MODEL_ID").ok();
+    let job_id = env::var("CLOUD_ML_JOB_ID").ok();
+    if deployed_model_id.is_none() && job_id.is_none() {
+        Err(OpenTelemetryError::new("AIP_DEPLOYED_MODEL_ID and CLOUD_ML_JOB_ID not found"))?
     }
 
+    let mut attrs = get_project_resources(context)?;
+    attrs.extend(get_zone_resources(metadata));
+    if let Some(serde_json::Value::String(host_id)) = get_val::get_val(metadata, &["instance", "id"], None) {
+        attrs.push(KeyValue::new("host.id", host_id.clone()));
+    }
+
+    if let Some(deployed_model_id) = deployed_model_id {
+        attrs.push(KeyValue::new(ResourceAttributes::VERTEX_AI_DEPLOYED_MODEL_ID, deployed_model_id));
+        if let Ok(endpoint_id) = env::var("AIP_ENDPOINT_ID") {
+            attrs.push(KeyValue::new(ResourceAttributes::VERTEX_AI_ENDPOINT_ID, endpoint_id));
+        }
+        attrs.push(KeyValue::new("gcp.resource_type", "vertex_ai_endpoint"));
+    } else if let Some(job_id) = job_id {
+        attrs.push(KeyValue::new(ResourceAttributes::VERTEX_AI_JOB_ID, job_id));
+        let cluster_spec = env::var("CLUSTER_SPEC").ok().and_then(|spec| serde_json::from_str::<serde_json::Value>(&spec).ok());
+        if let Some(cluster_spec) = cluster_spec {
+            if let Some(serde_json::Value::String(replica_type)) = get_val::get_val(&cluster_spec, &["task", "type"], None) {
+                attrs.push(KeyValue::new(ResourceAttributes::VERTEX_AI_REPLICA_TYPE, replica_type.clone()));
+            }
+            if let Some(replica_index) = get_val::get_val(&cluster_spec, &["task", "index"], None).and_then(|index| index.as_i64()) {
+                attrs.push(KeyValue::new(ResourceAttributes::VERTEX_AI_REPLICA_INDEX, replica_index));
+            }
+        }
+        attrs.push(KeyValue::new("gcp.resource_type", "vertex_ai_custom_job"));
+    }
+
+    attrs.push(KeyValue::new("cloud.platform", ResourceAttributes::GCP_VERTEX_AI));
+    Ok(attrs)
+}
+
+
+/// Resource finder for Cloud Workstations attributes
+fn get_workstations_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    if env::var("CLOUD_WORKSTATIONS_CONFIG_DIRECTORY").is_err() {
+        Err(OpenTelemetryError::new("CLOUD_WORKSTATIONS_CONFIG_DIRECTORY not found"))?
+    }
+
+    let mut attrs = get_project_resources(context)?;
+    attrs.extend(get_zone_resources(metadata));
+    if let Some(serde_json::Value::String(host_id)) = get_val::get_val(metadata, &["instance", "id"], None) {
+        attrs.push(KeyValue::new("host.id", host_id.clone()));
+    }
+
+    attrs.push(KeyValue::new("cloud.platform", "gcp_cloud_workstations"));
+    attrs.push(KeyValue::new("gcp.resource_type", "cloud_workstation"));
+    Ok(attrs)
+}
+
+
+/// Resource finder for Cloud Shell attributes
+///
+/// The Cloud Shell metadata server only serves a subset of the metadata,
+/// so the project falls back to the one selected in the shell.
+fn get_cloudshell_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    if env::var("CLOUD_SHELL").is_err() {
+        Err(OpenTelemetryError::new("CLOUD_SHELL not found"))?
+    }
+
+    let mut attrs = if let Ok(attrs) = get_metadata_resources(context) {
+        attrs
+    } else if let Ok(project_id) = env::var("GOOGLE_CLOUD_PROJECT").or_else(|_| env::var("DEVSHELL_PROJECT_ID")) {
+        vec![
+            KeyValue::new("cloud.account.id", project_id),
+            KeyValue::new("cloud.provider", "gcp"),
+        ]
+    } else {
+        vec![KeyValue::new("cloud.provider", "gcp")]
+    };
+    attrs.extend(get_zone_resources(metadata));
+
+    attrs.push(KeyValue::new("cloud.platform", "gcp_cloud_shell"));
+    attrs.push(KeyValue::new("gcp.resource_type", "cloud_shell"));
+    Ok(attrs)
+}
+
 
-    let mut attrs = get_metadata_resources(metadata)?;
+/// Resource finder for the pod attributes exposed to the container through the environment
+fn get_k8s_pod_resources() -> Vec<KeyValue> {
+    let mut attrs = vec![];
     if let Ok(container_name) = env::var("CONTAINER_NAME") {
-        attrs.push(KeyValue::new("container.name", container_name));
+        attrs.push(KeyValue::new("container.name", container_name.clone()));
+        attrs.push(KeyValue::new(ResourceAttributes::K8S_CONTAINER_NAME, container_name));
+    }
+    if let Ok(node_name) = env::var("NODE_NAME") {
+        attrs.push(KeyValue::new(ResourceAttributes::K8S_NODE_NAME, node_name));
     }
 
+    // Knative services (Cloud Run for Anthos) running on the cluster
+    if let Ok(faas_name) = env::var("K_SERVICE") {
+        attrs.push(KeyValue::new("faas.name", faas_name));
+    }
+    if let Ok(faas_version) = env::var("K_REVISION") {
+        attrs.push(KeyValue::new("faas.version", faas_version));
+    }
 
     let pod_namespace = if let Ok(pod_namespace) = env::var("NAMESPACE") {
         pod_namespace
@@ -138,10 +707,7 @@ fn get_gke_resources(metadata: &serde_json::Value) -> Result<Vec<KeyValue>, Open
             Ok(mut file) => {
                 let mut namespace: Vec<u8> = Vec::new();
                 if file.read_to_end(&mut namespace).is_ok() {
-                    let s = match str::from_utf8(&namespace) {
-                        Ok(v) => v,
-                        Err(e) => "",
-                    };
+                    let s = str::from_utf8(&namespace).unwrap_or_default();
                     s.trim().to_string()
                 } else {
                     String::new()
@@ -154,19 +720,32 @@ fn get_gke_resources(metadata: &serde_json::Value) -> Result<Vec<KeyValue>, Open
 
     let pod_name = if let Ok(pod_name) = env::var("POD_NAME") {
         pod_name
-    } else if let Ok(pod_name) = env::var("HOSTNAME") {
-        pod_name
     } else {
-        String::new()
+        env::var("HOSTNAME").unwrap_or_default()
     };
     attrs.push(KeyValue::new("k8s.pod.name", pod_name.clone()));
+    attrs
+}
+
 
-    let cluster_name = if let Some(serde_json::Value::String(cluster_name)) = get_val::get_val(metadata, &["instance", "attributes", "cluster-name"], None) {
-        cluster_name
+/// Resource finder for GKE attributes
+fn get_gke_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    if env::var("KUBERNETES_SERVICE_HOST").is_err() {
+        Err(OpenTelemetryError::new("KUBERNETES_SERVICE_HOST not found"))?
+    }
+    get_gke_resources_unchecked(context)
+}
+
+/// [`get_gke_resources`] without checking the environment
+fn get_gke_resources_unchecked(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    let mut attrs = get_project_resources(context)?;
+    attrs.extend(get_k8s_pod_resources());
+    if let Some(serde_json::Value::String(cluster_name)) = get_val::get_val(metadata, &["instance", "attributes", "cluster-name"], None) {
+        attrs.push(KeyValue::new("k8s.cluster.name", cluster_name.clone()));
     } else {
-        Err(OpenTelemetryError::new("cluster name not found"))?
-    };
-    attrs.push(KeyValue::new("k8s.cluster.name", cluster_name.clone()));
+        context.missing("instance.attributes.cluster-name")?
+    }
 
     if let Some(serde_json::Value::String(cluster_location)) = get_val::get_val(metadata, &["instance", "attributes", "cluster-location"], None) {
         let hyphen_count = cluster_location.split('-').count();
@@ -179,173 +758,583 @@ fn get_gke_resources(metadata: &serde_json::Value) -> Result<Vec<KeyValue>, Open
     }
 
     if let Some(serde_json::Value::String(zone)) = get_val::get_val(metadata, &["instance", "zone"], None) {
-        let zone = zone.split('/').last();
-        if let Some(r) = zone {
-            attrs.push(KeyValue::new("cloud.zone", r.to_string()));
-        } else {
-            Err(OpenTelemetryError::new("zone not found"))?
+        if let Some(zone) = zone.split('/').next_back() {
+            attrs.push(KeyValue::new("cloud.zone", zone.to_string()));
         }
     } else {
-        Err(OpenTelemetryError::new("zone not found"))?
-    };
+        context.missing("instance.zone")?
+    }
 
-    let host_id = if let Some(serde_json::Value::String(host_id)) = get_val::get_val(metadata, &["instance", "id"], None) {
-        host_id
+    if let Some(serde_json::Value::String(host_id)) = get_val::get_val(metadata, &["instance", "id"], None) {
+        attrs.push(KeyValue::new("host.id", host_id.clone()));
     } else {
-        Err(OpenTelemetryError::new("host id not found"))?
-    };
-    attrs.push(KeyValue::new("host.id", host_id.clone()));
+        context.missing("instance.id")?
+    }
+    attrs.extend(get_scheduling_resources(metadata));
 
     attrs.push(KeyValue::new("gcp.resource_type", "gke_container"));
     attrs.push(KeyValue::new("cloud.platform", "gcp_kubernetes_engine"));
 
     Ok(attrs)
-    
 }
 
 
-/// Resource finder for Cloud Run attributes
-fn get_cloudrun_resources(metadata: &serde_json::Value) -> Result<Vec<KeyValue>, OpenTelemetryError> {
-    if env::var("K_CONFIGURATION").is_err() {
-        Err(OpenTelemetryError::new("K_CONFIGURATION not found"))?
+/// Resource finder for self-managed Kubernetes clusters (e.g. kubeadm) running on GCE VMs.
+///
+/// The cluster name comes from `cluster_name` or the `K8S_CLUSTER_NAME` environment variable
+/// since these VMs don't have the `cluster-name` attribute of GKE nodes.
+fn get_self_managed_k8s_resources(context: &DetectionContext, cluster_name: Option<&str>) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    if env::var("KUBERNETES_SERVICE_HOST").is_err() {
+        Err(OpenTelemetryError::new("KUBERNETES_SERVICE_HOST not found"))?
     }
 
-    let mut attrs = get_metadata_resources(metadata)?;
-    if let Ok(faas_name) = env::var("K_SERVICE") {
-        attrs.push(KeyValue::new("faas.name", faas_name.clone()));
+    let mut attrs = get_gce_resources(context)?;
+    attrs.extend(get_k8s_pod_resources());
+
+    let cluster_name = cluster_name.map(|name| name.to_string()).or_else(|| env::var("K8S_CLUSTER_NAME").ok());
+    if let Some(cluster_name) = cluster_name {
+        attrs.push(KeyValue::new(ResourceAttributes::K8S_CLUSTER_NAME, cluster_name));
     }
+    Ok(attrs)
+}
 
-    if let Ok(faas_version) = env::var("K_REVISION") {
-        attrs.push(KeyValue::new("faas.version", faas_version.clone()));
+
+struct FleetMembership {
+    project: String,
+    location: String,
+    name: String,
+}
+
+/// Parse a fleet membership resource name, e.g. `projects/my-project/locations/global/memberships/my-cluster`
+fn parse_fleet_membership(text: &str) -> Option<FleetMembership> {
+    let re = Regex::new(r"^projects/(?P<project>[^/]+)/locations/(?P<location>[^/]+)/memberships/(?P<name>[^/]+)$").unwrap();
+    re.captures(text.trim()).map(|captures| FleetMembership {
+        project: captures["project"].to_string(),
+        location: captures["location"].to_string(),
+        name: captures["name"].to_string(),
+    })
+}
+
+/// Resource finder for GKE Enterprise clusters without a metadata server
+/// (attached clusters, GKE on-prem, Google Distributed Cloud).
+///
+/// Neither the cluster nor the fleet agent exposes the membership to the pods, so every input
+/// has to be provided by the user: the fleet membership name in the `FLEET_MEMBERSHIP`
+/// environment variable or in the file at `membership_path` (e.g. mounted from a ConfigMap),
+/// and/or the `GOOGLE_CLOUD_PROJECT`, `CLUSTER_LOCATION` and `K8S_CLUSTER_NAME` environment
+/// variables, which take precedence over the membership.
+fn get_attached_k8s_resources(context: &DetectionContext, cluster_name: Option<&str>, membership_path: Option<&Path>) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    if env::var("KUBERNETES_SERVICE_HOST").is_err() {
+        Err(OpenTelemetryError::new("KUBERNETES_SERVICE_HOST not found"))?
+    }
+    // Clusters on Google Cloud are detected from the metadata server instead
+    if !context.metadata.is_null() {
+        Err(OpenTelemetryError::new("metadata server available"))?
     }
 
-    if let Some(serde_json::Value::String(region)) = get_val::get_val(metadata, &["instance", "region"], None) {
-        let region = region.split('/').last();
-        if let Some(r) = region {
-            attrs.push(KeyValue::new("cloud.region", r.to_string()));
-        } else {
-            Err(OpenTelemetryError::new("region not found"))?
+    let membership_name = env::var("FLEET_MEMBERSHIP")
+        .ok()
+        .or_else(|| membership_path.and_then(|path| fs::read_to_string(path).ok()))
+        .map(|name| name.trim().to_string());
+    let membership = membership_name.as_deref().and_then(parse_fleet_membership);
+
+    let mut attrs = vec![KeyValue::new("cloud.provider", "gcp")];
+    if let Ok(project_id) = env::var("GOOGLE_CLOUD_PROJECT") {
+        attrs.push(KeyValue::new("cloud.account.id", project_id));
+    } else if let Some(membership) = &membership {
+        // The membership name may use the project number instead of the project id,
+        // which Google Cloud APIs accept as well
+        attrs.push(KeyValue::new("cloud.account.id", membership.project.clone()));
+        if membership.project.chars().all(|c| c.is_ascii_digit()) {
+            attrs.push(KeyValue::new(ResourceAttributes::GCP_PROJECT_NUMBER, membership.project.clone()));
         }
     } else {
-        Err(OpenTelemetryError::new("region not found"))?
-    };
+        Err(OpenTelemetryError::new("GOOGLE_CLOUD_PROJECT and fleet membership not found"))?
+    }
 
-    if let Some(serde_json::Value::String(zone)) = get_val::get_val(metadata, &["instance", "zone"], None) {
-        let zone = zone.split('/').last();
-        if let Some(r) = zone {
-            attrs.push(KeyValue::new("cloud.zone", r.to_string()));
+    let cluster_name = cluster_name
+        .map(|name| name.to_string())
+        .or_else(|| env::var("K8S_CLUSTER_NAME").ok())
+        .or_else(|| membership.as_ref().map(|membership| membership.name.clone()));
+    if let Some(cluster_name) = cluster_name {
+        attrs.push(KeyValue::new(ResourceAttributes::K8S_CLUSTER_NAME, cluster_name));
+    } else {
+        Err(OpenTelemetryError::new("K8S_CLUSTER_NAME and fleet membership not found"))?
+    }
+
+    let location = env::var("CLUSTER_LOCATION")
+        .ok()
+        .or_else(|| membership.as_ref().map(|membership| membership.location.clone()));
+    if let Some(location) = location.filter(|location| location != "global") {
+        let hyphen_count = location.split('-').count();
+        if hyphen_count == 3 {
+            attrs.push(KeyValue::new("cloud.availability_zone", location));
         } else {
-            Err(OpenTelemetryError::new("zone not found"))?
+            attrs.push(KeyValue::new("cloud.region", location));
         }
-    } else {
-        Err(OpenTelemetryError::new("zone not found"))?
-    };
+    }
 
-    let instance_id = if let Some(serde_json::Value::String(instance_id)) = get_val::get_val(metadata, &["instance", "id"], None) {
-        instance_id
-    } else {
-        Err(OpenTelemetryError::new("instance id not found"))?
-    };
-    attrs.push(KeyValue::new("faas.instance", instance_id.clone()));
+    if let Some(membership_name) = membership_name.filter(|_| membership.is_some()) {
+        attrs.push(KeyValue::new(ResourceAttributes::GCP_FLEET_MEMBERSHIP_NAME, membership_name));
+    }
 
-    attrs.push(KeyValue::new("cloud.platform", "gcp_cloud_run"));
-    attrs.push(KeyValue::new("gcp.resource_type", "cloud_run"));
+    attrs.extend(get_k8s_pod_resources());
+
+    attrs.push(KeyValue::new("gcp.resource_type", "gke_container"));
+    attrs.push(KeyValue::new("cloud.platform", "gcp_kubernetes_engine"));
     Ok(attrs)
 }
 
 
-/// Resource finder for Cloud Functions attributes
-fn get_cloudfunctions_resources(metadata: &serde_json::Value) -> Result<Vec<KeyValue>, OpenTelemetryError> {
-    if env::var("FUNCTION_TARGET").is_err() {
-        Err(OpenTelemetryError::new("FUNCTION_TARGET not found"))?
-    }
-
-    let mut attrs = get_metadata_resources(metadata)?;
-
+/// Resource finder for the attributes shared by Cloud Run and Cloud Functions
+fn get_faas_resources(context: &DetectionContext, platform: &str, resource_type: &str) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    let metadata = context.metadata;
+    let mut attrs = get_project_resources(context)?;
     if let Ok(faas_name) = env::var("K_SERVICE") {
-        attrs.push(KeyValue::new("faas.name", faas_name.clone()));
-    };
+        attrs.push(KeyValue::new("faas.name", faas_name));
+    }
     if let Ok(faas_version) = env::var("K_REVISION") {
-        attrs.push(KeyValue::new("faas.version", faas_version.clone()));
-    };
+        attrs.push(KeyValue::new("faas.version", faas_version));
+    }
 
     if let Some(serde_json::Value::String(region)) = get_val::get_val(metadata, &["instance", "region"], None) {
-        let region = region.split('/').last();
-        if let Some(r) = region {
-            attrs.push(KeyValue::new("cloud.region", r.to_string()));
-        } else {
-            Err(OpenTelemetryError::new("region not found"))?
+        if let Some(region) = region.split('/').next_back() {
+            attrs.push(KeyValue::new("cloud.region", region.to_string()));
         }
     } else {
-        Err(OpenTelemetryError::new("region not found"))?
-    };
+        context.missing("instance.region")?
+    }
 
     if let Some(serde_json::Value::String(zone)) = get_val::get_val(metadata, &["instance", "zone"], None) {
-        let zone = zone.split('/').last();
-        if let Some(r) = zone {
-            attrs.push(KeyValue::new("cloud.zone", r.to_string()));
-        } else {
-            Err(OpenTelemetryError::new("zone not found"))?
+        if let Some(zone) = zone.split('/').next_back() {
+            attrs.push(KeyValue::new("cloud.zone", zone.to_string()));
         }
     } else {
-        Err(OpenTelemetryError::new("zone not found"))?
-    };
+        context.missing("instance.zone")?
+    }
 
-    let instance_id = if let Some(serde_json::Value::String(instance_id)) = get_val::get_val(metadata, &["instance", "id"], None) {
-        instance_id
+    if let Some(serde_json::Value::String(instance_id)) = get_val::get_val(metadata, &["instance", "id"], None) {
+        attrs.push(KeyValue::new("faas.instance", instance_id.clone()));
     } else {
-        Err(OpenTelemetryError::new("instance id not found"))?
-    };
-    attrs.push(KeyValue::new("faas.instance", instance_id.clone()));
+        context.missing("instance.id")?
+    }
 
-    attrs.push(KeyValue::new("cloud.platform", "gcp_cloud_functions"));
-    attrs.push(KeyValue::new("gcp.resource_type", "cloud_functions"));
+    attrs.push(KeyValue::new("cloud.platform", platform.to_string()));
+    attrs.push(KeyValue::new("gcp.resource_type", resource_type.to_string()));
     Ok(attrs)
 }
 
 
+/// Resource finder for Cloud Run attributes
+fn get_cloudrun_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    if env::var("K_CONFIGURATION").is_err() {
+        Err(OpenTelemetryError::new("K_CONFIGURATION not found"))?
+    }
+    get_faas_resources(context, "gcp_cloud_run", "cloud_run")
+}
+
+
+/// Resource finder for Cloud Functions attributes
+fn get_cloudfunctions_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    if env::var("FUNCTION_TARGET").is_err() {
+        Err(OpenTelemetryError::new("FUNCTION_TARGET not found"))?
+    }
+    get_faas_resources(context, "gcp_cloud_functions", "cloud_functions")
+}
+
+
+/// Resource finder for a simulated platform, runs the platform detector on simulated metadata
+fn get_simulated_resources(platform: SimulatedPlatform, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    match platform {
+        SimulatedPlatform::CloudRun => get_faas_resources(context, "gcp_cloud_run", "cloud_run"),
+        SimulatedPlatform::CloudFunctions => get_faas_resources(context, "gcp_cloud_functions", "cloud_functions"),
+        SimulatedPlatform::Gke => get_gke_resources_unchecked(context),
+        SimulatedPlatform::Gce => get_gce_resources(context),
+    }
+}
+
+
+fn find_attr(attrs: &[KeyValue], key: &str) -> Option<String> {
+    attrs.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.to_string())
+}
+
+
 pub struct GoogleCloudResourceDetector {
     attrs: Arc<Vec<KeyValue>>,
+    report: Arc<DetectionReport>,
 }
 
-impl GoogleCloudResourceDetector {
-    pub async fn new() -> Self {
-        let metadata = match get_metadata().await {
-            Ok(metadata) => metadata,
+/// Builder for [`GoogleCloudResourceDetector`] with opt-in enrichments
+#[derive(Default)]
+pub struct GoogleCloudResourceDetectorBuilder {
+    k8s_workload: bool,
+    k8s_api: Option<K8sApiConfig>,
+    fs_root: Option<PathBuf>,
+    instance_labels: Option<InstanceLabelsConfig>,
+    otel_metadata_attributes: bool,
+    service_account: bool,
+    network: bool,
+    external_ip: bool,
+    self_managed_k8s: bool,
+    k8s_cluster_name: Option<String>,
+    attached_k8s: bool,
+    fleet_membership_path: Option<PathBuf>,
+    local_fallback: bool,
+    simulated_platform: Option<SimulatedPlatform>,
+    platform_detectors: Option<Vec<Box<dyn PlatformDetector>>>,
+    best_effort: bool,
+}
+
+impl GoogleCloudResourceDetectorBuilder {
+    /// Derive `k8s.deployment.name`, `k8s.statefulset.name`, `k8s.job.name`, ... on GKE
+    /// from the pod owner references, using the in-cluster service account.
+    pub fn with_k8s_workload(mut self, enabled: bool) -> Self {
+        self.k8s_workload = enabled;
+        self
+    }
+
+    /// Enable workload detection against a specific Kubernetes API server
+    pub fn with_k8s_api(mut self, config: K8sApiConfig) -> Self {
+        self.k8s_workload = true;
+        self.k8s_api = Some(config);
+        self
+    }
+
+    /// Root of the filesystem `/proc` is read from when looking up `container.id`
+    pub fn with_fs_root<T: Into<PathBuf>>(mut self, fs_root: T) -> Self {
+        self.fs_root = Some(fs_root.into());
+        self
+    }
+
+    /// Export GCE instance labels and allowlisted `instance/attributes/*` keys
+    pub fn with_instance_labels(mut self, config: InstanceLabelsConfig) -> Self {
+        self.instance_labels = Some(config);
+        self
+    }
+
+    /// Apply `otel-service-name`, `otel-service-namespace` and `otel-resource-attributes`
+    /// instance or project metadata attributes. Disabled by default, so binaries have to opt in
+    /// once before the attributes can be changed without redeploying them.
+    ///
+    /// They can't override any other detected attribute: the ones of the platform
+    /// (e.g. `cloud.platform` or `host.id`), instance labels and the service account.
+    pub fn with_otel_metadata_attributes(mut self, enabled: bool) -> Self {
+        self.otel_metadata_attributes = enabled;
+        self
+    }
+
+    /// Export `gcp.service_account.email` of the default service account. Disabled by default
+    /// since the email identifies the workload beyond what telemetry usually needs.
+    pub fn with_service_account(mut self, enabled: bool) -> Self {
+        self.service_account = enabled;
+        self
+    }
+
+    /// Export `host.ip`, `gcp.gce.network` and `gcp.gce.subnetwork` of the GCE network interfaces,
+    /// on GCE instances and the platforms running on them (TPU, Dataproc, Dataflow, Batch, ...)
+    pub fn with_network_attributes(mut self, enabled: bool) -> Self {
+        self.network = enabled;
+        self
+    }
+
+    /// Include external IPs in `host.ip`, only used with [`Self::with_network_attributes`]
+    pub fn with_external_ip(mut self, enabled: bool) -> Self {
+        self.external_ip = enabled;
+        self
+    }
+
+    /// Report pods of self-managed Kubernetes clusters on GCE VMs with the k8s attributes
+    /// on top of the GCE attributes, instead of as plain GCE instances.
+    pub fn with_self_managed_k8s(mut self, enabled: bool) -> Self {
+        self.self_managed_k8s = enabled;
+        self
+    }
+
+    /// Cluster name of a self-managed Kubernetes cluster, defaults to the `K8S_CLUSTER_NAME` environment variable
+    pub fn with_k8s_cluster_name<T: ToString>(mut self, cluster_name: T) -> Self {
+        self.k8s_cluster_name = Some(cluster_name.to_string());
+        self
+    }
+
+    /// Report pods of GKE Enterprise clusters without a metadata server (attached clusters,
+    /// GKE on-prem) from their fleet membership.
+    ///
+    /// These clusters don't expose the membership to the pods, it has to be provided through
+    /// the `FLEET_MEMBERSHIP` environment variable or [`with_fleet_membership_path`](Self::with_fleet_membership_path).
+    /// Without a membership, the project and cluster name are required from the `GOOGLE_CLOUD_PROJECT`
+    /// and `K8S_CLUSTER_NAME` environment variables, the location is read from `CLUSTER_LOCATION`.
+    pub fn with_attached_k8s(mut self, enabled: bool) -> Self {
+        self.attached_k8s = enabled;
+        self
+    }
+
+    /// File with the fleet membership name of an attached cluster,
+    /// e.g. `projects/my-project/locations/global/memberships/my-cluster`.
+    /// The file isn't provided by the platform, e.g. mount it from a ConfigMap.
+    /// The `FLEET_MEMBERSHIP` environment variable takes precedence.
+    pub fn with_fleet_membership_path<T: Into<PathBuf>>(mut self, path: T) -> Self {
+        self.fleet_membership_path = Some(path.into());
+        self
+    }
+
+    /// Outside of Google Cloud, e.g. on a developer machine, report a `generic_node` resource
+    /// with the project from `GOOGLE_CLOUD_PROJECT`, the application default credentials or the
+    /// active gcloud configuration, so exports to Cloud Monitoring aren't rejected.
+    pub fn with_local_fallback(mut self, enabled: bool) -> Self {
+        self.local_fallback = enabled;
+        self
+    }
+
+    /// Don't detect the platform, simulate `platform` with the project, region, zone and instance id
+    /// from the `OTEL_GCP_*` environment variables instead. Only meant for local testing,
+    /// defaults to the `OTEL_GCP_PLATFORM` environment variable.
+    /// The simulated resource is marked with `gcp.simulated=true`.
+    pub fn with_simulated_platform(mut self, platform: SimulatedPlatform) -> Self {
+        self.simulated_platform = Some(platform);
+        self
+    }
+
+    /// Replace the built-in platform detectors, the first detector returning `Ok` wins.
+    /// Start from [`platform::default_detectors`] to add, remove or reorder detectors.
+    ///
+    /// The detector options of this builder (e.g. [`Self::with_k8s_cluster_name`]) only
+    /// apply to the built-in detectors, set them on the detectors passed here instead.
+    /// The opt-in detectors are replaced as well: add [`platform::SelfManagedK8sDetector`],
+    /// [`platform::AttachedK8sDetector`] or [`platform::LocalDetector`] to `detectors` rather than
+    /// combining this with [`Self::with_self_managed_k8s`], [`Self::with_attached_k8s`] or
+    /// [`Self::with_local_fallback`], which panics in debug builds.
+    pub fn with_platform_detectors(mut self, detectors: Vec<Box<dyn PlatformDetector>>) -> Self {
+        self.platform_detectors = Some(detectors);
+        self
+    }
+
+    /// Once the identifying signal of a platform is found (e.g. `K_CONFIGURATION` on Cloud Run),
+    /// omit the required attributes missing from the metadata instead of rejecting the platform.
+    /// The missing attributes are listed in the [`DetectionReport`].
+    ///
+    /// Platforms identified by the environment alone (Cloud Run, Cloud Functions) are always
+    /// detected this way when the metadata server isn't reachable.
+    pub fn with_best_effort(mut self, enabled: bool) -> Self {
+        self.best_effort = enabled;
+        self
+    }
+
+    fn get_simulated_platform(&self) -> Option<SimulatedPlatform> {
+        if self.simulated_platform.is_some() {
+            return self.simulated_platform;
+        }
+        let platform = env::var("OTEL_GCP_PLATFORM").ok()?;
+        match platform.parse() {
+            Ok(platform) => Some(platform),
             Err(e) => {
-                warn!("Failed to get metadata: {:?}", e);
-                return Self {
-                    attrs: Arc::new(vec![]),
-                };
+                warn!("Ignoring OTEL_GCP_PLATFORM: {}", e);
+                None
             }
+        }
+    }
+
+    async fn push_k8s_workload(&self, attrs: &mut Vec<KeyValue>) {
+        if self.k8s_workload {
+            let config = self.k8s_api.clone().unwrap_or_else(K8sApiConfig::in_cluster);
+            let namespace = find_attr(attrs, ResourceAttributes::K8S_NAMESPACE_NAME).unwrap_or_default();
+            let pod_name = find_attr(attrs, ResourceAttributes::K8S_POD_NAME).unwrap_or_default();
+            attrs.extend(k8s_workload::get_workload_resources(&config, &namespace, &pod_name).await);
+        }
+    }
+
+    fn fs_root(&self) -> PathBuf {
+        self.fs_root.clone().unwrap_or_else(|| PathBuf::from("/"))
+    }
+
+    fn push_container_id(&self, attrs: &mut Vec<KeyValue>) {
+        if let Some(container_id) = container_id::get_container_id(&self.fs_root()) {
+            attrs.push(KeyValue::new(ResourceAttributes::CONTAINER_ID, container_id));
+        }
+    }
+
+    pub async fn build(self) -> GoogleCloudResourceDetector {
+        let (attrs, report) = if let Some(platform) = self.get_simulated_platform() {
+            self.detect_simulated(platform)
+        } else {
+            let started = Instant::now();
+            let metadata = get_metadata().await;
+            let fetch_duration = started.elapsed();
+            let (metadata, metadata_error) = match metadata {
+                Ok(metadata) => (metadata, None),
+                Err(e) => (serde_json::Value::Null, Some(e.to_string())),
+            };
+            let (attrs, mut report) = self.detect_with_report(&metadata).await;
+            report.metadata_fetch_duration = Some(fetch_duration);
+            report.metadata_error = metadata_error;
+            (attrs, report)
         };
+        if attrs.is_empty() {
+            warn!("No resource found, {}", report);
+        } else {
+            debug!("Resource detection: {}", report);
+        }
+        GoogleCloudResourceDetector {
+            attrs: Arc::new(attrs),
+            report: Arc::new(report),
+        }
+    }
 
+    /// Resource of the simulated `platform`, with the enrichments applied to the simulated metadata
+    fn detect_simulated(&self, platform: SimulatedPlatform) -> (Vec<KeyValue>, DetectionReport) {
+        warn!(
+            "Simulating the {} platform, the resource is NOT detected and must not be used in production",
+            platform
+        );
+        let mut report = DetectionReport {
+            simulated_platform: Some(platform.to_string()),
+            ..Default::default()
+        };
+        let metadata = simulation::simulated_metadata(platform);
+        match get_simulated_resources(platform, &DetectionContext::new(&metadata)) {
+            Ok(mut attrs) => {
+                attrs.push(KeyValue::new(ResourceAttributes::GCP_SIMULATED, true));
+                report.matched(&platform.to_string(), vec![]);
+                (self.enrich(&metadata, attrs), report)
+            }
+            Err(e) => {
+                report.rejected(&platform.to_string(), e);
+                (vec![], report)
+            }
+        }
+    }
+
+    /// The built-in detectors, configured with the builder options
+    pub(crate) fn default_platform_detectors(&self) -> Vec<Box<dyn PlatformDetector>> {
+        let mut detectors: Vec<Box<dyn PlatformDetector>> = vec![
+            // Vertex AI containers run in Google-managed GKE clusters, so they are checked before GKE.
+            Box::new(platform::VertexAiDetector),
+            Box::new(platform::CloudWorkstationsDetector),
+            Box::new(platform::CloudShellDetector),
+            Box::new(platform::GkeDetector),
+        ];
+        if self.self_managed_k8s {
+            detectors.push(Box::new(platform::SelfManagedK8sDetector {
+                cluster_name: self.k8s_cluster_name.clone(),
+            }));
+        }
+        detectors.push(Box::new(platform::CloudRunDetector));
+        detectors.push(Box::new(platform::CloudFunctionsDetector));
+        // Cloud Build restricts the metadata server, but can be detected from the environment
+        detectors.push(Box::new(platform::CloudBuildDetector));
+        detectors.push(Box::new(platform::BatchDetector));
+        detectors.push(Box::new(platform::TpuDetector));
+        detectors.push(Box::new(platform::DataprocDetector));
+        detectors.push(Box::new(platform::DataflowDetector));
+        detectors.push(Box::new(platform::GceDetector));
+        if self.attached_k8s {
+            detectors.push(Box::new(platform::AttachedK8sDetector {
+                cluster_name: self.k8s_cluster_name.clone(),
+                membership_path: self.fleet_membership_path.clone(),
+            }));
+        }
+        if self.local_fallback {
+            detectors.push(Box::new(platform::LocalDetector));
+        }
+        detectors
+    }
+
+    /// Run the platform detectors on `metadata`, which is `Value::Null` when the metadata server isn't reachable
+    async fn detect(&self, metadata: &serde_json::Value) -> Vec<KeyValue> {
+        self.detect_with_report(metadata).await.0
+    }
+
+    async fn detect_with_report(&self, metadata: &serde_json::Value) -> (Vec<KeyValue>, DetectionReport) {
         // Order here matters. Since a GKE_CONTAINER is a specialized type of GCE_INSTANCE
         // We need to first check if it matches the criteria for being a GKE_CONTAINER
         // before falling back and checking if its a GCE_INSTANCE.
         // This list should be sorted from most specialized to least specialized.
-        let attrs = if let Ok(kv) = get_gke_resources(&metadata) {
-            kv
-        } else if let Ok(kv) = get_cloudrun_resources(&metadata) {
-            kv
-        } else if let Ok(kv) = get_cloudfunctions_resources(&metadata) {
-            kv
-        } else if let Ok(kv) = get_gce_resources(&metadata) {
-            kv
+        let default_detectors;
+        let detectors = if let Some(detectors) = &self.platform_detectors {
+            debug_assert!(
+                !(self.self_managed_k8s || self.attached_k8s || self.local_fallback),
+                "the opt-in detectors are ignored with custom platform detectors, add them to the detectors instead"
+            );
+            detectors
         } else {
-            warn!("No resource found");
-            return Self {
-                attrs: Arc::new(vec![]),
-            };
+            default_detectors = self.default_platform_detectors();
+            &default_detectors
         };
-        Self {
-            attrs: Arc::new(attrs),
+
+        let mut report = DetectionReport::default();
+        for detector in detectors {
+            let best_effort = self.best_effort || (metadata.is_null() && detector.detects_without_metadata());
+            let context = DetectionContext::new(metadata).with_best_effort(best_effort);
+            let result = detector.detect(&context);
+            let missing = context.take_missing();
+            let mut attrs = match result {
+                Ok(attrs) => attrs,
+                Err(e) => {
+                    report.rejected(detector.name(), e);
+                    continue;
+                }
+            };
+            report.matched(detector.name(), missing);
+            if attrs.iter().any(|kv| kv.key.as_str() == ResourceAttributes::K8S_POD_NAME) {
+                self.push_k8s_workload(&mut attrs).await;
+            }
+            if detector.runs_in_container() {
+                self.push_container_id(&mut attrs);
+            }
+            return (self.enrich(metadata, attrs), report);
+        }
+        (vec![], report)
+    }
+
+    /// Apply the opt-in enrichments to the detected platform attributes
+    fn enrich(&self, metadata: &serde_json::Value, mut attrs: Vec<KeyValue>) -> Vec<KeyValue> {
+        // Every platform reported as GCE runs on a VM with its own network interfaces and GPUs
+        if find_attr(&attrs, ResourceAttributes::CLOUD_PLATFORM_KEY).as_deref() == Some(ResourceAttributes::GCP_COMPUTE_ENGINE) {
+            if self.network {
+                attrs.extend(get_network_resources(metadata, self.external_ip));
+            }
+            // The driver knows the GPUs better than the machine type does
+            for kv in get_nvidia_gpu_resources(&self.fs_root()) {
+                otel_metadata::set_attr(&mut attrs, kv);
+            }
+        }
+        if let Some(config) = &self.instance_labels {
+            attrs.extend(instance_labels::get_instance_labels_resources(metadata, config));
+        }
+        if self.service_account {
+            attrs.extend(get_service_account_resources(metadata));
         }
+        // Applied last, they only add attributes that nothing else found
+        let detected_keys: Vec<_> = attrs.iter().map(|kv| kv.key.clone()).collect();
+        if self.otel_metadata_attributes {
+            for kv in otel_metadata::get_otel_metadata_resources(metadata) {
+                if detected_keys.contains(&kv.key) {
+                    warn!("Ignoring {} of the otel metadata attributes, it is already detected", kv.key);
+                    continue;
+                }
+                otel_metadata::set_attr(&mut attrs, kv);
+            }
+        }
+        attrs
+    }
+}
+
+impl GoogleCloudResourceDetector {
+    pub async fn new() -> Self {
+        Self::builder().build().await
+    }
+
+    pub fn builder() -> GoogleCloudResourceDetectorBuilder {
+        GoogleCloudResourceDetectorBuilder::default()
     }
 
     pub fn get_resource(&self) -> Resource {
         Resource::new(self.attrs.as_ref().clone())
     }
+
+    /// How the resource was detected, e.g. to log why no platform matched
+    pub fn report(&self) -> &DetectionReport {
+        &self.report
+    }
 }
 
 impl ResourceDetector for GoogleCloudResourceDetector {
@@ -0,0 +1,147 @@
+use std::{env, fs, path::{Path, PathBuf}};
+
+use opentelemetry::KeyValue;
+
+use crate::{constants::ResourceAttributes, error::OpenTelemetryError, platform::DetectionContext};
+
+/// `project_id` of a service account key or ADC file
+fn project_from_credentials(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let credentials = serde_json::from_str::<serde_json::Value>(&content).ok()?;
+    match credentials.get("project_id") {
+        Some(serde_json::Value::String(project_id)) if !project_id.is_empty() => Some(project_id.clone()),
+        _ => None,
+    }
+}
+
+/// Value of `key` in `section` of a gcloud properties file (INI syntax)
+fn get_property(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut current_section = "";
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            current_section = name.trim();
+            continue;
+        }
+        if current_section != section {
+            continue;
+        }
+        if let Some((name, value)) = line.split_once('=') {
+            if name.trim() == key && !value.trim().is_empty() {
+                return Some(value.trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+/// gcloud configuration directory, `CLOUDSDK_CONFIG` or the platform default
+fn gcloud_config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = env::var("CLOUDSDK_CONFIG") {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        env::var("APPDATA").ok().map(|dir| PathBuf::from(dir).join("gcloud"))
+    } else {
+        env::var("HOME").ok().map(|dir| PathBuf::from(dir).join(".config").join("gcloud"))
+    }
+}
+
+/// `core/project` of the gcloud configuration `active_config`, or of the one activated in `config_dir`
+fn project_from_gcloud(config_dir: &Path, active_config: Option<String>) -> Option<String> {
+    let active_config = active_config
+        .or_else(|| fs::read_to_string(config_dir.join("active_config")).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "default".to_string());
+    let path = config_dir.join("configurations").join(format!("config_{}", active_config));
+    let content = fs::read_to_string(path).ok()?;
+    get_property(&content, "core", "project")
+}
+
+fn get_hostname() -> Option<String> {
+    env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+/// Resource finder for local development, outside of Google Cloud.
+///
+/// The project is resolved like the client libraries do: `GOOGLE_CLOUD_PROJECT`, then
+/// `project_id` of the `GOOGLE_APPLICATION_CREDENTIALS` file, then `CLOUDSDK_CORE_PROJECT`
+/// and the active gcloud configuration.
+/// The resource maps to a `generic_node` monitored resource.
+pub(crate) fn get_local_resources(context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+    if !context.metadata.is_null() {
+        Err(OpenTelemetryError::new("metadata server available"))?
+    }
+    let project_id = env::var("GOOGLE_CLOUD_PROJECT")
+        .ok()
+        .or_else(|| env::var("GOOGLE_APPLICATION_CREDENTIALS").ok().and_then(|path| project_from_credentials(Path::new(&path))))
+        .or_else(|| env::var("CLOUDSDK_CORE_PROJECT").ok())
+        .or_else(|| gcloud_config_dir().and_then(|dir| project_from_gcloud(&dir, env::var("CLOUDSDK_ACTIVE_CONFIG_NAME").ok())));
+    let project_id = if let Some(project_id) = project_id {
+        project_id
+    } else {
+        Err(OpenTelemetryError::new("project id not found"))?
+    };
+
+    let mut attrs = vec![
+        KeyValue::new(ResourceAttributes::CLOUD_ACCOUNT_ID, project_id),
+        KeyValue::new(ResourceAttributes::CLOUD_PROVIDER, "gcp"),
+    ];
+    if let Some(hostname) = get_hostname() {
+        attrs.push(KeyValue::new(ResourceAttributes::HOST_NAME, hostname));
+    }
+    attrs.push(KeyValue::new("gcp.resource_type", "generic_node"));
+    Ok(attrs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("otel_gcp_local_project_{}_{}", std::process::id(), name));
+        fs::create_dir_all(dir.join("configurations")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_get_property() {
+        let content = "[core]\naccount = me@example.com\n# project = commented\nproject = my-project\n\n[compute]\nproject = other\n";
+        assert_eq!(get_property(content, "core", "project"), Some("my-project".to_string()));
+        assert_eq!(get_property(content, "core", "zone"), None);
+        assert_eq!(get_property("[core]\nproject =\n", "core", "project"), None);
+    }
+
+    #[test]
+    fn test_project_from_credentials() {
+        let dir = fixture_dir("credentials");
+        let path = dir.join("key.json");
+        fs::write(&path, r#"{"type": "service_account", "project_id": "sa-project"}"#).unwrap();
+        assert_eq!(project_from_credentials(&path), Some("sa-project".to_string()));
+
+        fs::write(&path, r#"{"type": "authorized_user", "client_id": "id"}"#).unwrap();
+        assert_eq!(project_from_credentials(&path), None);
+        assert_eq!(project_from_credentials(&dir.join("missing.json")), None);
+    }
+
+    #[test]
+    fn test_project_from_gcloud_active_config() {
+        let dir = fixture_dir("gcloud");
+        fs::write(dir.join("active_config"), "work\n").unwrap();
+        fs::write(dir.join("configurations").join("config_default"), "[core]\nproject = default-project\n").unwrap();
+        fs::write(dir.join("configurations").join("config_work"), "[core]\nproject = work-project\n").unwrap();
+        assert_eq!(project_from_gcloud(&dir, None), Some("work-project".to_string()));
+        assert_eq!(project_from_gcloud(&dir, Some("default".to_string())), Some("default-project".to_string()));
+
+        fs::remove_file(dir.join("active_config")).unwrap();
+        assert_eq!(project_from_gcloud(&dir, None), Some("default-project".to_string()));
+    }
+}
@@ -184,6 +184,62 @@ static MAPPINFS: Lazy<HashMap<&str, HashMap<&str, MapConfig>>> = Lazy::new(|| {
                 ),
             ]),
         ),
+        // Vertex AI has no monitored resource accepting custom metrics, its endpoints
+        // and custom jobs are written as `generic_task`
+        (
+            constants::VERTEX_AI_ENDPOINT,
+            HashMap::from([
+                (
+                    constants::LOCATION,
+                    MapConfig {
+                        otel_keys: vec![
+                            ResourceAttributes::CLOUD_AVAILABILITY_ZONE,
+                            ResourceAttributes::CLOUD_REGION,
+                        ],
+                        fallback: "global",
+                    },
+                ),
+                (
+                    constants::NAMESPACE,
+                    MapConfig::new(vec![ResourceAttributes::VERTEX_AI_ENDPOINT_ID]),
+                ),
+                (
+                    constants::JOB,
+                    MapConfig::new(vec![ResourceAttributes::VERTEX_AI_DEPLOYED_MODEL_ID]),
+                ),
+                (
+                    constants::TASK_ID,
+                    MapConfig::new(vec![ResourceAttributes::HOST_ID]),
+                ),
+            ]),
+        ),
+        (
+            constants::VERTEX_AI_CUSTOM_JOB,
+            HashMap::from([
+                (
+                    constants::LOCATION,
+                    MapConfig {
+                        otel_keys: vec![
+                            ResourceAttributes::CLOUD_AVAILABILITY_ZONE,
+                            ResourceAttributes::CLOUD_REGION,
+                        ],
+                        fallback: "global",
+                    },
+                ),
+                (
+                    constants::NAMESPACE,
+                    MapConfig::new(vec![ResourceAttributes::VERTEX_AI_JOB_ID]),
+                ),
+                (
+                    constants::JOB,
+                    MapConfig::new(vec![ResourceAttributes::VERTEX_AI_REPLICA_TYPE]),
+                ),
+                (
+                    constants::TASK_ID,
+                    MapConfig::new(vec![ResourceAttributes::HOST_ID]),
+                ),
+            ]),
+        ),
         (
             constants::GENERIC_NODE,
             HashMap::from([
@@ -224,7 +280,16 @@ fn create_monitored_resource(
     monitored_resource_type: &str, 
     resource_attrs: HashMap<String, opentelemetry::Value>
 ) -> MonitoredResourceData {
-    let mapping = MAPPINFS.get(monitored_resource_type).unwrap();
+    create_monitored_resource_with(monitored_resource_type, monitored_resource_type, resource_attrs)
+}
+
+/// Like [`create_monitored_resource`], with the labels of the `mapping` entry of [`MAPPINFS`]
+fn create_monitored_resource_with(
+    mapping: &str,
+    monitored_resource_type: &str,
+    resource_attrs: HashMap<String, opentelemetry::Value>
+) -> MonitoredResourceData {
+    let mapping = MAPPINFS.get(mapping).unwrap();
     let mut labels = HashMap::new();
 
     for (mr_key, map_config) in mapping.iter() {
@@ -268,6 +333,19 @@ fn create_monitored_resource(
 
 
 
+fn create_k8s_monitored_resource(attrs: HashMap<String, opentelemetry::Value>) -> MonitoredResourceData {
+    if attrs.contains_key(ResourceAttributes::K8S_CONTAINER_NAME) {
+        create_monitored_resource(constants::K8S_CONTAINER, attrs)
+    } else if attrs.contains_key(ResourceAttributes::K8S_POD_NAME) {
+        create_monitored_resource(constants::K8S_POD, attrs)
+    } else if attrs.contains_key(ResourceAttributes::K8S_NODE_NAME) {
+        create_monitored_resource(constants::K8S_NODE, attrs)
+    } else {
+        create_monitored_resource(constants::K8S_CLUSTER, attrs)
+    }
+}
+
+
 /// Add Google resource specific information (e.g. instance id, region).
 /// 
 /// See
@@ -284,20 +362,29 @@ pub fn get_monitored_resource(
     });
     let attrs: HashMap<String, opentelemetry::Value> = HashMap::from_iter(res_atters);
     let platform = attrs.get(ResourceAttributes::CLOUD_PLATFORM_KEY).map(|v| v.to_string());
-    let platform = platform.as_ref().map(|v| v.as_str());
+    let platform = platform.as_deref();
     let mr = match platform {
         Some(ResourceAttributes::GCP_COMPUTE_ENGINE) => {
-            create_monitored_resource(constants::GCE_INSTANCE, attrs)
+            // Pods of self-managed kubernetes clusters running on GCE VMs
+            if attrs.contains_key(ResourceAttributes::K8S_POD_NAME) {
+                create_k8s_monitored_resource(attrs)
+            } else {
+                create_monitored_resource(constants::GCE_INSTANCE, attrs)
+            }
         },
         Some(ResourceAttributes::GCP_KUBERNETES_ENGINE) => {
-            if attrs.get(ResourceAttributes::K8S_CONTAINER_NAME).is_some() {
-                create_monitored_resource(constants::K8S_CONTAINER, attrs)
-            } else if attrs.get(ResourceAttributes::K8S_POD_NAME).is_some() {
-                create_monitored_resource(constants::K8S_POD, attrs)
-            } else if attrs.get(ResourceAttributes::K8S_NODE_NAME).is_some() {
-                create_monitored_resource(constants::K8S_NODE, attrs)
-            } else {
-                create_monitored_resource(constants::K8S_CLUSTER, attrs)
+            create_k8s_monitored_resource(attrs)
+        },
+        Some(ResourceAttributes::GCP_VERTEX_AI) => {
+            let resource_type = attrs.get(ResourceAttributes::GCP_RESOURCE_TYPE).map(|v| v.to_string());
+            match resource_type.as_deref() {
+                Some(constants::VERTEX_AI_ENDPOINT) => {
+                    create_monitored_resource_with(constants::VERTEX_AI_ENDPOINT, constants::GENERIC_TASK, attrs)
+                },
+                Some(constants::VERTEX_AI_CUSTOM_JOB) => {
+                    create_monitored_resource_with(constants::VERTEX_AI_CUSTOM_JOB, constants::GENERIC_TASK, attrs)
+                },
+                _ => create_monitored_resource(constants::GENERIC_NODE, attrs),
             }
         },
         Some(ResourceAttributes::AWS_EC2) => {
@@ -305,7 +392,7 @@ pub fn get_monitored_resource(
         },
         _ => {
             // fallback to generic_task
-            if( attrs.get(ResourceAttributes::SERVICE_NAME).is_some() || attrs.get(ResourceAttributes::FAAS_NAME).is_some()) && (attrs.get(ResourceAttributes::SERVICE_INSTANCE_ID).is_some() || attrs.get(ResourceAttributes::FAAS_INSTANCE).is_some()) {
+            if( attrs.contains_key(ResourceAttributes::SERVICE_NAME) || attrs.contains_key(ResourceAttributes::FAAS_NAME)) && (attrs.contains_key(ResourceAttributes::SERVICE_INSTANCE_ID) || attrs.contains_key(ResourceAttributes::FAAS_INSTANCE)) {
                 create_monitored_resource(constants::GENERIC_TASK, attrs)
             } else {
                 create_monitored_resource(constants::GENERIC_NODE, attrs)
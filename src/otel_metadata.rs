@@ -0,0 +1,111 @@
+use opentelemetry::KeyValue;
+use percent_encoding::percent_decode_str;
+
+use crate::{constants::ResourceAttributes, get_val};
+
+const OTEL_SERVICE_NAME: &str = "otel-service-name";
+const OTEL_SERVICE_NAMESPACE: &str = "otel-service-namespace";
+const OTEL_RESOURCE_ATTRIBUTES: &str = "otel-resource-attributes";
+
+/// Parse a list of `key1=value1,key2=value2` pairs with the `OTEL_RESOURCE_ATTRIBUTES` syntax.
+/// Values are percent-decoded, invalid pairs are skipped.
+pub(crate) fn parse_resource_attributes(text: &str) -> Vec<KeyValue> {
+    text.split(',')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() {
+                return None;
+            }
+            let value = percent_decode_str(value.trim()).decode_utf8().ok()?;
+            Some(KeyValue::new(key.to_string(), value.into_owned()))
+        })
+        .collect()
+}
+
+/// Put `kv` into `attrs`, replacing an attribute with the same key
+pub(crate) fn set_attr(attrs: &mut Vec<KeyValue>, kv: KeyValue) {
+    attrs.retain(|existing| existing.key != kv.key);
+    attrs.push(kv);
+}
+
+fn get_level_resources(metadata: &serde_json::Value, level: &str) -> Vec<KeyValue> {
+    let mut attrs = vec![];
+    let get_attribute = |key: &str| match get_val::get_val(metadata, &[level, "attributes", key], None) {
+        Some(serde_json::Value::String(value)) => Some(value.clone()),
+        _ => None,
+    };
+    if let Some(resource_attributes) = get_attribute(OTEL_RESOURCE_ATTRIBUTES) {
+        for kv in parse_resource_attributes(&resource_attributes) {
+            set_attr(&mut attrs, kv);
+        }
+    }
+    if let Some(service_namespace) = get_attribute(OTEL_SERVICE_NAMESPACE) {
+        set_attr(&mut attrs, KeyValue::new(ResourceAttributes::SERVICE_NAMESPACE, service_namespace));
+    }
+    if let Some(service_name) = get_attribute(OTEL_SERVICE_NAME) {
+        set_attr(&mut attrs, KeyValue::new(ResourceAttributes::SERVICE_NAME, service_name));
+    }
+    attrs
+}
+
+/// Resource finder for `otel-service-name`, `otel-service-namespace` and `otel-resource-attributes`
+/// set as project or instance metadata attributes. Instance level values override project level ones.
+pub(crate) fn get_otel_metadata_resources(metadata: &serde_json::Value) -> Vec<KeyValue> {
+    let mut attrs = get_level_resources(metadata, "project");
+    for kv in get_level_resources(metadata, "instance") {
+        set_attr(&mut attrs, kv);
+    }
+    attrs
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry_sdk::Resource;
+    use pretty_assertions_sorted::assert_eq_sorted;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_attributes() {
+        let attrs = parse_resource_attributes(" team = pay%20ments ,invalid,=nokey,url=http%3A%2F%2Fexample.com%2Fa%2Cb,empty=");
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("team", "pay ments"),
+            KeyValue::new("url", "http://example.com/a,b"),
+            KeyValue::new("empty", ""),
+        ]);
+        assert_eq_sorted!(Resource::new(attrs), res_sould_be);
+    }
+
+    #[test]
+    fn test_instance_overrides_project() {
+        let metadata = serde_json::json!({
+            "project": {
+                "attributes": {
+                    "otel-service-name": "project-service",
+                    "otel-service-namespace": "project-namespace",
+                    "otel-resource-attributes": "team=platform,tier=backend",
+                },
+            },
+            "instance": {
+                "attributes": {
+                    "otel-service-name": "instance-service",
+                    "otel-resource-attributes": "team=payments,service.name=ignored",
+                },
+            },
+        });
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("service.name", "instance-service"),
+            KeyValue::new("service.namespace", "project-namespace"),
+            KeyValue::new("team", "payments"),
+            KeyValue::new("tier", "backend"),
+        ]);
+        assert_eq_sorted!(Resource::new(get_otel_metadata_resources(&metadata)), res_sould_be);
+    }
+
+    #[test]
+    fn test_no_attributes() {
+        let metadata = serde_json::json!({"project": {"projectId": "p"}, "instance": {}});
+        assert!(get_otel_metadata_resources(&metadata).is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+use std::{cell::RefCell, env, path::PathBuf};
+
+use opentelemetry::KeyValue;
+
+use crate::{error::OpenTelemetryError, local_project};
+
+/// Input of a [`PlatformDetector`]
+#[derive(Debug)]
+pub struct DetectionContext<'a> {
+    /// Response of the metadata server, `Value::Null` when it isn't reachable
+    pub metadata: &'a serde_json::Value,
+    /// Omit required attributes that can't be found instead of rejecting the platform
+    pub best_effort: bool,
+    missing: RefCell<Vec<String>>,
+}
+
+impl<'a> DetectionContext<'a> {
+    pub fn new(metadata: &'a serde_json::Value) -> Self {
+        Self {
+            metadata,
+            best_effort: false,
+            missing: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn with_best_effort(mut self, enabled: bool) -> Self {
+        self.best_effort = enabled;
+        self
+    }
+
+    /// Report a required attribute that can't be found, which rejects the platform unless in best-effort mode.
+    pub fn missing(&self, field: &str) -> Result<(), OpenTelemetryError> {
+        if !self.best_effort {
+            Err(OpenTelemetryError::new(format!("{} not found", field)))?
+        }
+        let mut missing = self.missing.borrow_mut();
+        if !missing.iter().any(|missing| missing == field) {
+            missing.push(field.to_string());
+        }
+        Ok(())
+    }
+
+    /// Missing attributes reported since the last call
+    pub(crate) fn take_missing(&self) -> Vec<String> {
+        self.missing.take()
+    }
+}
+
+/// Detector of the platform the process runs on.
+///
+/// [`crate::GoogleCloudResourceDetectorBuilder`] runs the detectors in order and uses the
+/// attributes of the first one returning `Ok`, so more specialized platforms go first.
+pub trait PlatformDetector: Send + Sync {
+    /// Name of the platform, used in logs
+    fn name(&self) -> &str;
+
+    /// Resource attributes of the platform, or an error telling why the process doesn't run on it
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError>;
+
+    /// Whether the platform runs the process in a container, `container.id` is then added
+    fn runs_in_container(&self) -> bool {
+        false
+    }
+
+    /// Whether the environment alone identifies the platform, which is then detected in
+    /// best-effort mode when the metadata server isn't reachable
+    fn detects_without_metadata(&self) -> bool {
+        false
+    }
+}
+
+/// Vertex AI prediction and custom training containers
+#[derive(Debug, Clone, Default)]
+pub struct VertexAiDetector;
+
+impl PlatformDetector for VertexAiDetector {
+    fn name(&self) -> &str {
+        "vertex_ai"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_vertex_ai_resources(context)
+    }
+
+    fn runs_in_container(&self) -> bool {
+        true
+    }
+}
+
+/// Cloud Workstations
+#[derive(Debug, Clone, Default)]
+pub struct CloudWorkstationsDetector;
+
+impl PlatformDetector for CloudWorkstationsDetector {
+    fn name(&self) -> &str {
+        "cloud_workstations"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_workstations_resources(context)
+    }
+}
+
+/// Cloud Shell
+#[derive(Debug, Clone, Default)]
+pub struct CloudShellDetector;
+
+impl PlatformDetector for CloudShellDetector {
+    fn name(&self) -> &str {
+        "cloud_shell"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_cloudshell_resources(context)
+    }
+}
+
+/// GKE pods
+#[derive(Debug, Clone, Default)]
+pub struct GkeDetector;
+
+impl PlatformDetector for GkeDetector {
+    fn name(&self) -> &str {
+        "gke"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_gke_resources(context)
+    }
+
+    fn runs_in_container(&self) -> bool {
+        true
+    }
+}
+
+/// Pods of self-managed Kubernetes clusters (e.g. kubeadm) on GCE VMs
+#[derive(Debug, Clone, Default)]
+pub struct SelfManagedK8sDetector {
+    /// Cluster name, defaults to the `K8S_CLUSTER_NAME` environment variable
+    pub cluster_name: Option<String>,
+}
+
+impl PlatformDetector for SelfManagedK8sDetector {
+    fn name(&self) -> &str {
+        "self_managed_k8s"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_self_managed_k8s_resources(context, self.cluster_name.as_deref())
+    }
+
+    fn runs_in_container(&self) -> bool {
+        true
+    }
+}
+
+/// Cloud Run services and jobs, also detected without the metadata server
+#[derive(Debug, Clone, Default)]
+pub struct CloudRunDetector;
+
+impl PlatformDetector for CloudRunDetector {
+    fn name(&self) -> &str {
+        "cloud_run"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_cloudrun_resources(context)
+    }
+
+    fn runs_in_container(&self) -> bool {
+        true
+    }
+
+    fn detects_without_metadata(&self) -> bool {
+        true
+    }
+}
+
+/// Cloud Functions, also detected without the metadata server
+#[derive(Debug, Clone, Default)]
+pub struct CloudFunctionsDetector;
+
+impl PlatformDetector for CloudFunctionsDetector {
+    fn name(&self) -> &str {
+        "cloud_functions"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_cloudfunctions_resources(context)
+    }
+
+    fn runs_in_container(&self) -> bool {
+        true
+    }
+
+    fn detects_without_metadata(&self) -> bool {
+        true
+    }
+}
+
+/// Cloud Build steps, also detected without the metadata server
+#[derive(Debug, Clone, Default)]
+pub struct CloudBuildDetector;
+
+impl PlatformDetector for CloudBuildDetector {
+    fn name(&self) -> &str {
+        "cloud_build"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_cloudbuild_resources(context)
+    }
+}
+
+/// Cloud Batch tasks
+#[derive(Debug, Clone, Default)]
+pub struct BatchDetector;
+
+impl PlatformDetector for BatchDetector {
+    fn name(&self) -> &str {
+        "batch"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_batch_resources(context)
+    }
+}
+
+/// Cloud TPU VMs
+#[derive(Debug, Clone, Default)]
+pub struct TpuDetector;
+
+impl PlatformDetector for TpuDetector {
+    fn name(&self) -> &str {
+        "tpu"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_tpu_resources(context)
+    }
+}
+
+/// Dataproc cluster nodes
+#[derive(Debug, Clone, Default)]
+pub struct DataprocDetector;
+
+impl PlatformDetector for DataprocDetector {
+    fn name(&self) -> &str {
+        "dataproc"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_dataproc_resources(context)
+    }
+}
+
+/// Dataflow workers
+#[derive(Debug, Clone, Default)]
+pub struct DataflowDetector;
+
+impl PlatformDetector for DataflowDetector {
+    fn name(&self) -> &str {
+        "dataflow"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_dataflow_resources(context)
+    }
+}
+
+/// GCE instances
+#[derive(Debug, Clone, Default)]
+pub struct GceDetector;
+
+impl PlatformDetector for GceDetector {
+    fn name(&self) -> &str {
+        "gce"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        // Developer environments run on GCE VMs, but must not be reported as production instances,
+        // even when their detectors aren't in the detector list.
+        if env::var("CLOUD_WORKSTATIONS_CONFIG_DIRECTORY").is_ok() {
+            Err(OpenTelemetryError::new("CLOUD_WORKSTATIONS_CONFIG_DIRECTORY set"))?
+        }
+        if env::var("CLOUD_SHELL").is_ok() {
+            Err(OpenTelemetryError::new("CLOUD_SHELL set"))?
+        }
+        crate::get_gce_resources(context)
+    }
+}
+
+/// GKE Enterprise clusters without a metadata server (attached clusters, GKE on-prem)
+#[derive(Debug, Clone, Default)]
+pub struct AttachedK8sDetector {
+    /// Cluster name, defaults to the `K8S_CLUSTER_NAME` environment variable or the fleet membership name
+    pub cluster_name: Option<String>,
+    /// File with the fleet membership name, provided by the user (e.g. mounted from a ConfigMap).
+    /// The `FLEET_MEMBERSHIP` environment variable takes precedence.
+    pub membership_path: Option<PathBuf>,
+}
+
+impl PlatformDetector for AttachedK8sDetector {
+    fn name(&self) -> &str {
+        "attached_k8s"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        crate::get_attached_k8s_resources(context, self.cluster_name.as_deref(), self.membership_path.as_deref())
+    }
+
+    fn runs_in_container(&self) -> bool {
+        true
+    }
+}
+
+/// Local development outside of Google Cloud, without a metadata server
+#[derive(Debug, Clone, Default)]
+pub struct LocalDetector;
+
+impl PlatformDetector for LocalDetector {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+        local_project::get_local_resources(context)
+    }
+}
+
+/// The built-in detectors, sorted from most specialized to least specialized.
+///
+/// The opt-in [`SelfManagedK8sDetector`], [`AttachedK8sDetector`] and [`LocalDetector`] aren't included.
+pub fn default_detectors() -> Vec<Box<dyn PlatformDetector>> {
+    crate::GoogleCloudResourceDetectorBuilder::default().default_platform_detectors()
+}
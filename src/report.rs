@@ -0,0 +1,108 @@
+use std::{fmt, time::Duration};
+
+/// Outcome of one [`crate::platform::PlatformDetector`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformAttempt {
+    /// Name of the platform detector
+    pub platform: String,
+    /// Why the detector rejected the platform, `None` if it matched
+    pub rejection: Option<String>,
+    /// Required attributes omitted in best-effort mode
+    pub missing: Vec<String>,
+}
+
+/// What happened during resource detection, see [`crate::GoogleCloudResourceDetector::report`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetectionReport {
+    /// Time spent fetching the metadata server, `None` if it wasn't fetched
+    pub metadata_fetch_duration: Option<Duration>,
+    /// Why the metadata server couldn't be fetched
+    pub metadata_error: Option<String>,
+    /// Platform forced with [`crate::GoogleCloudResourceDetectorBuilder::with_simulated_platform`]
+    pub simulated_platform: Option<String>,
+    /// Platform detectors in the order they ran, up to the matching one
+    pub attempts: Vec<PlatformAttempt>,
+}
+
+impl DetectionReport {
+    /// Name of the matching platform, `None` if no platform was detected
+    pub fn platform(&self) -> Option<&str> {
+        self.attempts
+            .iter()
+            .find(|attempt| attempt.rejection.is_none())
+            .map(|attempt| attempt.platform.as_str())
+    }
+
+    pub(crate) fn rejected<T: ToString>(&mut self, platform: &str, reason: T) {
+        self.attempts.push(PlatformAttempt {
+            platform: platform.to_string(),
+            rejection: Some(reason.to_string()),
+            missing: vec![],
+        });
+    }
+
+    pub(crate) fn matched(&mut self, platform: &str, missing: Vec<String>) {
+        self.attempts.push(PlatformAttempt {
+            platform: platform.to_string(),
+            rejection: None,
+            missing,
+        });
+    }
+}
+
+impl fmt::Display for DetectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.metadata_fetch_duration, &self.metadata_error) {
+            (Some(duration), None) => write!(f, "metadata fetched in {:?}", duration)?,
+            (Some(duration), Some(error)) => write!(f, "metadata fetch failed after {:?}: {}", duration, error)?,
+            (None, _) => write!(f, "metadata not fetched")?,
+        }
+        if let Some(platform) = &self.simulated_platform {
+            write!(f, ", simulated platform {}", platform)?;
+        }
+        write!(f, ", detected platform: {}", self.platform().unwrap_or("none"))?;
+        for attempt in &self.attempts {
+            match &attempt.rejection {
+                Some(reason) => write!(f, "\n  {}: {}", attempt.platform, reason)?,
+                None if attempt.missing.is_empty() => write!(f, "\n  {}: matched", attempt.platform)?,
+                None => write!(f, "\n  {}: matched, missing {}", attempt.platform, attempt.missing.join(", "))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let mut report = DetectionReport {
+            metadata_fetch_duration: Some(Duration::from_millis(12)),
+            ..Default::default()
+        };
+        report.rejected("vertex_ai", "AIP_DEPLOYED_MODEL_ID and CLOUD_ML_JOB_ID not found");
+        report.matched("gke", vec!["instance.zone".to_string(), "instance.id".to_string()]);
+        assert_eq!(report.platform(), Some("gke"));
+        assert_eq!(
+            report.to_string(),
+            "metadata fetched in 12ms, detected platform: gke\n  vertex_ai: AIP_DEPLOYED_MODEL_ID and CLOUD_ML_JOB_ID not found\n  gke: matched, missing instance.zone, instance.id"
+        );
+    }
+
+    #[test]
+    fn test_display_without_platform() {
+        let mut report = DetectionReport {
+            metadata_fetch_duration: Some(Duration::from_millis(3)),
+            metadata_error: Some("connection refused".to_string()),
+            ..Default::default()
+        };
+        report.rejected("gce", "project.projectId not found");
+        assert_eq!(report.platform(), None);
+        assert_eq!(
+            report.to_string(),
+            "metadata fetch failed after 3ms: connection refused, detected platform: none\n  gce: project.projectId not found"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::{env, fmt, str::FromStr};
+
+use crate::error::OpenTelemetryError;
+
+/// Platform whose resource is simulated with [`crate::GoogleCloudResourceDetectorBuilder::with_simulated_platform`]
+/// or the `OTEL_GCP_PLATFORM` environment variable, to test the resource locally without deploying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulatedPlatform {
+    CloudRun,
+    CloudFunctions,
+    Gke,
+    Gce,
+}
+
+impl FromStr for SimulatedPlatform {
+    type Err = OpenTelemetryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "cloud_run" => Ok(Self::CloudRun),
+            "cloud_functions" => Ok(Self::CloudFunctions),
+            "gke" => Ok(Self::Gke),
+            "gce" => Ok(Self::Gce),
+            _ => Err(OpenTelemetryError::new(format!("unknown platform {}, expected cloud_run, cloud_functions, gke or gce", s))),
+        }
+    }
+}
+
+impl fmt::Display for SimulatedPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::CloudRun => "cloud_run",
+            Self::CloudFunctions => "cloud_functions",
+            Self::Gke => "gke",
+            Self::Gce => "gce",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn env_or(key: &str, default: &str) -> String {
+    env::var(key).unwrap_or_else(|_| default.to_string())
+}
+
+/// Metadata server response for `platform`, built from the `OTEL_GCP_PROJECT_ID`, `OTEL_GCP_PROJECT_NUMBER`,
+/// `OTEL_GCP_REGION`, `OTEL_GCP_ZONE`, `OTEL_GCP_INSTANCE_ID`, `OTEL_GCP_INSTANCE_NAME` and
+/// `OTEL_GCP_CLUSTER_NAME` environment variables, with placeholder defaults.
+pub(crate) fn simulated_metadata(platform: SimulatedPlatform) -> serde_json::Value {
+    let project_id = env::var("OTEL_GCP_PROJECT_ID").unwrap_or_else(|_| env_or("GOOGLE_CLOUD_PROJECT", "simulated-project"));
+    let project_number = env_or("OTEL_GCP_PROJECT_NUMBER", "000000000000");
+    let region = env_or("OTEL_GCP_REGION", "us-central1");
+    let zone = env::var("OTEL_GCP_ZONE").unwrap_or_else(|_| format!("{}-a", region));
+    let instance_id = env_or("OTEL_GCP_INSTANCE_ID", "0000000000000000000");
+
+    let mut metadata = serde_json::json!({
+        "project": {
+            "projectId": project_id,
+            "numericProjectId": project_number,
+        },
+        "instance": {
+            "id": instance_id,
+            "zone": format!("projects/{}/zones/{}", project_number, zone),
+        },
+    });
+    let instance = &mut metadata["instance"];
+    match platform {
+        SimulatedPlatform::CloudRun | SimulatedPlatform::CloudFunctions => {
+            instance["region"] = format!("projects/{}/regions/{}", project_number, region).into();
+        }
+        SimulatedPlatform::Gke => {
+            instance["attributes"] = serde_json::json!({
+                "cluster-name": env_or("OTEL_GCP_CLUSTER_NAME", "simulated-cluster"),
+                "cluster-location": region,
+            });
+        }
+        SimulatedPlatform::Gce => {
+            instance["name"] = env_or("OTEL_GCP_INSTANCE_NAME", "simulated-instance").into();
+            instance["machineType"] = format!("projects/{}/machineTypes/e2-medium", project_number).into();
+        }
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_platform() {
+        assert_eq!("cloud_run".parse::<SimulatedPlatform>().unwrap(), SimulatedPlatform::CloudRun);
+        assert_eq!(" GKE ".parse::<SimulatedPlatform>().unwrap(), SimulatedPlatform::Gke);
+        assert!("app_engine".parse::<SimulatedPlatform>().is_err());
+        for platform in [SimulatedPlatform::CloudRun, SimulatedPlatform::CloudFunctions, SimulatedPlatform::Gke, SimulatedPlatform::Gce] {
+            assert_eq!(platform.to_string().parse::<SimulatedPlatform>().unwrap(), platform);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 
+use crate::platform::DetectionContext;
 use crate::error::OpenTelemetryError;
 use opentelemetry::KeyValue;
 use opentelemetry_sdk::Resource;
@@ -15,7 +16,10 @@ mod gce_gke_tests {
     // use pretty_assertions::{assert_eq, assert_ne};
     use pretty_assertions_sorted::{assert_eq, assert_eq_sorted};
     use regex::Regex;
-    use crate::{get_gce_resources, get_gke_resources, test_envs::TestEnvs};
+    use crate::{get_dataflow_resources, get_gce_resources, get_gke_resources, get_gpu_resources, get_tpu_resources, parse_zone, test_envs::TestEnvs, GoogleCloudResourceDetector};
+    use crate::test_fixtures::FixtureDir;
+    use crate::instance_labels::InstanceLabelsConfig;
+    use crate::test_k8s_workload::block_on;
 
 
     #[test]
@@ -29,7 +33,7 @@ mod gce_gke_tests {
                 "zone": "projects/233510669999/zones/us-east4-b",
             },
         });
-        let resources = get_gce_resources(&metadata).unwrap();
+        let resources = get_gce_resources(&DetectionContext::new(&metadata)).unwrap();
         let res_default = Resource::default();
         let res = Resource::new(resources);
         // let res = res.merge(&res_default);
@@ -43,6 +47,7 @@ mod gce_gke_tests {
             KeyValue::new("host.id", "fakeId".to_string()),
             KeyValue::new("host.name", "fakeName".to_string()),
             KeyValue::new("host.type", "fakeMachineType".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "fakeName".to_string()),
             // KeyValue::new("service.name", "unknown_service"),
             // KeyValue::new("telemetry.sdk.language", "rust"),
             // KeyValue::new("telemetry.sdk.name", "opentelemetry"),
@@ -52,132 +57,858 @@ mod gce_gke_tests {
     }
 
     #[test]
-    fn test_get_gke_resources_regional() {
-        let resources = {  
-            let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","POD_NAME","HOSTNAME","NAMESPACE"]);
+    fn test_get_gce_resources_hostname_and_image() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "hostname": "fakeName.us-east4-b.c.fakeProject.internal",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "image": "projects/debian-cloud/global/images/debian-12-bookworm-v20240110",
+            },
+        });
+        let resources = get_gce_resources(&DetectionContext::new(&metadata)).unwrap();
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.gce.image.project", "debian-cloud".to_string()),
+            KeyValue::new("gcp.gce.instance.hostname", "fakeName.us-east4-b.c.fakeProject.internal".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "fakeName".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.image.id", "projects/debian-cloud/global/images/debian-12-bookworm-v20240110".to_string()),
+            KeyValue::new("host.image.name", "debian-12-bookworm-v20240110".to_string()),
+            KeyValue::new("host.name", "fakeName".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
 
-            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
-            TestEnvs::set_var("NAMESPACE", "namespace");
-            TestEnvs::set_var("HOSTNAME", "host_name");
-            TestEnvs::set_var("POD_NAME", "pod_name");
+    #[test]
+    fn test_gce_resources_with_instance_labels() {
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET"]);
 
             let metadata = serde_json::json!({
+                "project": {"projectId": "fakeProject"},
                 "instance": {
-                    "id": "instance_id",
+                    "name": "fakeName",
+                    "id": "fakeId",
+                    "machineType": "fakeMachineType",
                     "zone": "projects/233510669999/zones/us-east4-b",
-                    "attributes": {
-                        "cluster-name": "cluster_name",
-                        "cluster-location": "us-east4",
-                    },
+                    "labels": {"env": "staging", "team": "payments"},
+                    "attributes": {"role": "api", "ssh-keys": "secret"},
                 },
-                "project": {"projectId": "project_id"},
             });
-            get_gke_resources(&metadata).unwrap()
+            let builder = GoogleCloudResourceDetector::builder()
+                .with_instance_labels(InstanceLabelsConfig::default().with_attributes(&["role"]));
+            block_on(builder.detect(&metadata))
         };
-        let res_default = Resource::default();
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
         let res_sould_be = Resource::new(vec![
-            KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
-            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
-            KeyValue::new("host.id", "instance_id".to_string()),
-            KeyValue::new("k8s.pod.name", "pod_name".to_string()),
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
             KeyValue::new("cloud.region", "us-east4".to_string()),
-            KeyValue::new("cloud.zone", "us-east4-b".to_string()),
-            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("deployment.environment", "staging".to_string()),
+            KeyValue::new("gcp.gce.instance.attributes.role", "api".to_string()),
+            KeyValue::new("gcp.gce.instance.labels.env", "staging".to_string()),
+            KeyValue::new("gcp.gce.instance.labels.team", "payments".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "fakeName".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "fakeName".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_gce_resources_with_otel_metadata_attributes() {
+        let metadata = serde_json::json!({
+            "project": {
+                "projectId": "fakeProject",
+                "attributes": {"otel-service-name": "project-service", "otel-service-namespace": "shop"},
+            },
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {
+                    "otel-service-name": "checkout",
+                    "otel-resource-attributes": "deployment.environment=prod,team=pay%20ments",
+                },
+            },
+        });
+        let (resources, disabled) = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET"]);
+
+            let builder = GoogleCloudResourceDetector::builder().with_otel_metadata_attributes(true);
+            let disabled = GoogleCloudResourceDetector::builder();
+            (block_on(builder.detect(&metadata)), block_on(disabled.detect(&metadata)))
+        };
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("deployment.environment", "prod".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "fakeName".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "fakeName".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+            KeyValue::new("service.name", "checkout".to_string()),
+            KeyValue::new("service.namespace", "shop".to_string()),
+            KeyValue::new("team", "pay ments".to_string()),
         ]);
         assert_eq_sorted!(res, res_sould_be);
+        assert_eq!(Resource::new(disabled).get("service.name".into()), None);
     }
 
     #[test]
-    fn test_get_gke_resources_zone() {
-        let resources = {  
+    fn test_otel_metadata_attributes_dont_override_platform_attributes() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {
+                    "otel-resource-attributes": "cloud.platform=gcp_cloud_run,cloud.account.id=otherProject,host.id=spoofed,team=payments",
+                },
+            },
+        });
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET"]);
+
+            let builder = GoogleCloudResourceDetector::builder().with_otel_metadata_attributes(true);
+            block_on(builder.detect(&metadata))
+        };
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "fakeName".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "fakeName".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+            KeyValue::new("team", "payments".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_otel_metadata_attributes_dont_override_labels_and_service_account() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "labels": {"env": "prod"},
+                "service-accounts": {"default": {"email": "app@fakeProject.iam.gserviceaccount.com"}},
+                "attributes": {
+                    "otel-resource-attributes": "deployment.environment=dev,gcp.service_account.email=spoofed,team=payments",
+                },
+            },
+        });
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET"]);
+
+            let builder = GoogleCloudResourceDetector::builder()
+                .with_instance_labels(InstanceLabelsConfig::default())
+                .with_service_account(true)
+                .with_otel_metadata_attributes(true);
+            block_on(builder.detect(&metadata))
+        };
+        let res = Resource::new(resources);
+        assert_eq!(res.get("deployment.environment".into()), Some("prod".into()));
+        assert_eq!(res.get("gcp.service_account.email".into()), Some("app@fakeProject.iam.gserviceaccount.com".into()));
+        assert_eq!(res.get("team".into()), Some("payments".into()));
+    }
+
+    #[test]
+    fn test_get_gce_resources_project_number() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject", "numericProjectId": 233510669999u64},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        let resources = get_gce_resources(&DetectionContext::new(&metadata)).unwrap();
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "fakeName".to_string()),
+            KeyValue::new("gcp.project.number", "233510669999".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "fakeName".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_get_gce_resources_project_number_mismatch() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject", "numericProjectId": "111111111111"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        let resources = get_gce_resources(&DetectionContext::new(&metadata)).unwrap();
+        // numericProjectId wins over the project number of the zone path
+        let res = Resource::new(resources);
+        assert_eq!(res.get("gcp.project.number".into()), Some("111111111111".into()));
+        assert_eq!(res.get("cloud.availability_zone".into()), Some("us-east4-b".into()));
+    }
+
+    #[test]
+    fn test_parse_zone_project_number() {
+        let zone = parse_zone("projects/233510669999/zones/us-east4-b");
+        assert_eq!(zone.project_number, "233510669999");
+        assert_eq!(zone.region, "us-east4");
+        assert_eq!(zone.zone, "us-east4-b");
+        assert_eq!(parse_zone("zone").project_number, "");
+    }
+
+    #[test]
+    fn test_get_gce_resources_spot_scheduling() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "scheduling": {
+                    "automaticRestart": "FALSE",
+                    "onHostMaintenance": "TERMINATE",
+                    "preemptible": "TRUE",
+                    "provisioningModel": "SPOT",
+                },
+            },
+        });
+        let resources = get_gce_resources(&DetectionContext::new(&metadata)).unwrap();
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "fakeName".to_string()),
+            KeyValue::new("gcp.gce.instance.scheduling.on_host_maintenance", "TERMINATE".to_string()),
+            KeyValue::new("gcp.gce.instance.scheduling.preemptible", true),
+            KeyValue::new("gcp.gce.instance.scheduling.provisioning_model", "SPOT".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "fakeName".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_get_gke_resources_standard_scheduling() {
+        let resources = {
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","POD_NAME","HOSTNAME","NAMESPACE"]);
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME","POD_NAME","HOSTNAME","NAMESPACE"]);
 
             TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
             TestEnvs::set_var("NAMESPACE", "namespace");
-            TestEnvs::set_var("HOSTNAME", "host_name");
             TestEnvs::set_var("POD_NAME", "pod_name");
 
             let metadata = serde_json::json!({
                 "instance": {
                     "id": "instance_id",
                     "zone": "projects/233510669999/zones/us-east4-b",
-                    "attributes": {
-                        "cluster-name": "cluster_name",
-                        "cluster-location": "us-east4-b",
+                    "attributes": {"cluster-name": "cluster_name"},
+                    "scheduling": {
+                        "onHostMaintenance": "MIGRATE",
+                        "preemptible": "FALSE",
+                        "provisioningModel": "STANDARD",
                     },
                 },
                 "project": {"projectId": "project_id"},
             });
-            get_gke_resources(&metadata).unwrap()
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
         };
-        let res_default = Resource::default();
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
+
         let res_sould_be = Resource::new(vec![
             KeyValue::new("cloud.account.id", "project_id".to_string()),
             KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
             KeyValue::new("k8s.namespace.name", "namespace".to_string()),
             KeyValue::new("host.id", "instance_id".to_string()),
             KeyValue::new("k8s.pod.name", "pod_name".to_string()),
-            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.zone", "us-east4-b".to_string()),
             KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("cloud.zone", "us-east4-b".to_string()),
+            KeyValue::new("gcp.gce.instance.scheduling.on_host_maintenance", "MIGRATE".to_string()),
+            KeyValue::new("gcp.gce.instance.scheduling.preemptible", false),
+            KeyValue::new("gcp.gce.instance.scheduling.provisioning_model", "STANDARD".to_string()),
             KeyValue::new("gcp.resource_type", "gke_container"),
         ]);
         assert_eq_sorted!(res, res_sould_be);
     }
-}
-
-// @patch_env
-// @mock.patch(
-//     "opentelemetry.resourcedetector.gcp_resource_detector.requests.get",
-//     **{"return_value.json.return_value": GKE_RESOURCES_JSON_STRING}
-// )
-// class TestGKEResourceFinder(unittest.TestCase):
 
-//     # pylint: disable=unused-argument
-//     def test_not_running_on_gke(self, getter):
-//         pop_environ_key(KUBERNETES_SERVICE_HOST)
-//         found_resources = get_gke_resources()
-//         self.assertEqual(found_resources, {})
+    #[test]
+    fn test_gce_resources_with_network_attributes() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "networkInterfaces": [
+                    {
+                        "ip": "10.128.0.2",
+                        "network": "projects/233510669999/networks/default",
+                        "subnetwork": "projects/233510669999/regions/us-east4/subnetworks/default-east4",
+                        "accessConfigs": [{"externalIp": "34.1.2.3", "type": "ONE_TO_ONE_NAT"}],
+                    },
+                    {"ip": "10.10.0.5", "network": "projects/233510669999/networks/backend"},
+                ],
+            },
+        });
+        let (internal, external) = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET"]);
 
-//     # pylint: disable=unused-argument
-//     def test_missing_container_name(self, getter):
-//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
-//         pop_environ_key(CONTAINER_NAME)
-//         found_resources = get_gke_resources()
-//         self.assertEqual(
-//             found_resources,
-//             {
-//                 "cloud.account.id": "project_id",
-//                 "k8s.cluster.name": "cluster_name",
-//                 "k8s.namespace.name": "",
-//                 "host.id": "instance_id",
-//                 "k8s.pod.name": "",
-//                 "cloud.zone": "zone",
-//                 "cloud.provider": "gcp",
-//                 "gcp.resource_type": "gke_container",
-//             },
-//         )
+            let internal = GoogleCloudResourceDetector::builder().with_network_attributes(true);
+            let external = GoogleCloudResourceDetector::builder().with_network_attributes(true).with_external_ip(true);
+            (block_on(internal.detect(&metadata)), block_on(external.detect(&metadata)))
+        };
+        let res = Resource::new(internal);
+        let ips = opentelemetry::Value::Array(vec![opentelemetry::StringValue::from("10.128.0.2"), "10.10.0.5".into()].into());
+        assert_eq!(res.get("host.ip".into()), Some(ips));
+        assert_eq!(res.get("gcp.gce.network".into()), Some("default".into()));
+        assert_eq!(res.get("gcp.gce.subnetwork".into()), Some("default-east4".into()));
+
+        let res = Resource::new(external);
+        let ips = opentelemetry::Value::Array(vec![opentelemetry::StringValue::from("10.128.0.2"), "34.1.2.3".into(), "10.10.0.5".into()].into());
+        assert_eq!(res.get("host.ip".into()), Some(ips));
+    }
 
-//     # pylint: disable=unused-argument
-//     def test_environment_empty_strings(self, getter):
-//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
-//         os.environ[CONTAINER_NAME] = ""
-//         os.environ[NAMESPACE] = ""
-//         found_resources = get_gke_resources()
+    #[test]
+    fn test_dataproc_resources_with_network_attributes() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "cluster-m",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {"dataproc-cluster-name": "cluster"},
+                "networkInterfaces": [
+                    {
+                        "ip": "10.128.0.2",
+                        "network": "projects/233510669999/networks/default",
+                        "subnetwork": "projects/233510669999/regions/us-east4/subnetworks/default-east4",
+                    },
+                ],
+            },
+        });
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET", "BATCH_JOB_ID", "BATCH_JOB_UID"]);
+
+            let builder = GoogleCloudResourceDetector::builder().with_network_attributes(true);
+            block_on(builder.detect(&metadata))
+        };
+        let res = Resource::new(resources);
+        assert_eq!(res.get("gcp.resource_type".into()), Some("cloud_dataproc_cluster".into()));
+        let ips = opentelemetry::Value::Array(vec![opentelemetry::StringValue::from("10.128.0.2")].into());
+        assert_eq!(res.get("host.ip".into()), Some(ips));
+        assert_eq!(res.get("gcp.gce.network".into()), Some("default".into()));
+        assert_eq!(res.get("gcp.gce.subnetwork".into()), Some("default-east4".into()));
+    }
+
+    #[test]
+    fn test_best_effort_gce_resources() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(vec!["CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "id": "fakeId",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        let strict = get_gce_resources(&DetectionContext::new(&metadata));
+        assert_eq!(strict.unwrap_err().to_string(), "instance.machineType not found");
+
+        let context = DetectionContext::new(&metadata).with_best_effort(true);
+        let resources = get_gce_resources(&context).unwrap();
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject"),
+            KeyValue::new("cloud.availability_zone", "us-east4-b"),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4"),
+            KeyValue::new("host.id", "fakeId"),
+        ]);
+        assert_eq_sorted!(Resource::new(resources), res_sould_be);
+        assert_eq!(context.take_missing(), vec!["instance.machineType".to_string(), "instance.name".to_string()]);
+
+        // The instance id identifies a GCE instance, it is required in best-effort mode too
+        let metadata = serde_json::json!({"project": {"projectId": "fakeProject"}, "instance": {}});
+        assert!(get_gce_resources(&DetectionContext::new(&metadata).with_best_effort(true)).is_err());
+    }
+
+    #[test]
+    fn test_best_effort_gce_resources_reports_missing_zone_once() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(vec!["CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {"id": "fakeId", "machineType": "projects/233510669999/machineTypes/e2-medium", "name": "fakeName"},
+        });
+        let context = DetectionContext::new(&metadata).with_best_effort(true);
+        assert!(get_gce_resources(&context).is_ok());
+        assert_eq!(context.take_missing(), vec!["instance.zone".to_string()]);
+    }
+
+    #[test]
+    fn test_get_tpu_resources() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "t1v-n-1234-w-1",
+                "id": "fakeId",
+                "machineType": "projects/233510669999/machineTypes/ct4p-hightpu-4t",
+                "zone": "projects/233510669999/zones/us-central2-b",
+                "attributes": {
+                    "accelerator-type": "v4-32",
+                    "agent-worker-number": "1",
+                    "worker-network-endpoints": "unused:unused:10.130.0.2,unused:unused:10.130.0.3,unused:unused:10.130.0.4,unused:unused:10.130.0.5",
+                    "tpu-env": "ACCELERATOR_TYPE: 'v4-32'\nTOPOLOGY: '2x2x4'\nWORKER_ID: '1'\n",
+                },
+            },
+        });
+        let resources = get_tpu_resources(&DetectionContext::new(&metadata)).unwrap();
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-central2-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-central2".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "t1v-n-1234-w-1".to_string()),
+            KeyValue::new("gcp.resource_type", "tpu_worker"),
+            KeyValue::new("gcp.tpu.accelerator_type", "v4-32".to_string()),
+            KeyValue::new("gcp.tpu.topology", "2x2x4".to_string()),
+            KeyValue::new("gcp.tpu.worker.count", 4),
+            KeyValue::new("gcp.tpu.worker.id", 1),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "t1v-n-1234-w-1".to_string()),
+            KeyValue::new("host.type", "projects/233510669999/machineTypes/ct4p-hightpu-4t".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_not_running_on_tpu() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        assert!(get_tpu_resources(&DetectionContext::new(&metadata)).is_err());
+    }
+
+    #[test]
+    fn test_get_gce_resources_gpu_machine_type() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "projects/233510669999/machineTypes/a2-highgpu-4g",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        let res = Resource::new(get_gce_resources(&DetectionContext::new(&metadata)).unwrap());
+        assert_eq!(res.get("gcp.gce.accelerator.type".into()), Some("nvidia-tesla-a100".into()));
+        assert_eq!(res.get("gcp.gce.accelerator.count".into()), Some(4.into()));
+
+        let res = Resource::new(get_gpu_resources("g2-standard-24"));
+        assert_eq!(res.get("gcp.gce.accelerator.type".into()), Some("nvidia-l4".into()));
+        assert_eq!(res.get("gcp.gce.accelerator.count".into()), Some(2.into()));
+        assert!(get_gpu_resources("projects/233510669999/machineTypes/n1-standard-8").is_empty());
+        assert!(get_gpu_resources("g2-standard-2").is_empty());
+    }
+
+    #[test]
+    fn test_gce_resources_n1_with_attached_gpus() {
+        let root = FixtureDir::new("nvidia_gpus");
+        let information = "Model: \t\t Tesla T4\nIRQ:   \t\t 37\nBus Type: \t PCIe\n";
+        root.write("proc/driver/nvidia/gpus/0000:00:04.0/information", information);
+        root.write("proc/driver/nvidia/gpus/0000:00:05.0/information", information);
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "projects/233510669999/machineTypes/n1-standard-8",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        let (with_driver, without_driver) = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET", "CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+
+            let builder = GoogleCloudResourceDetector::builder().with_fs_root(&root.path);
+            let without_driver = GoogleCloudResourceDetector::builder().with_fs_root(root.path.join("empty"));
+            (block_on(builder.detect(&metadata)), block_on(without_driver.detect(&metadata)))
+        };
+        let res = Resource::new(with_driver);
+        assert_eq!(res.get("gcp.gce.accelerator.type".into()), Some("nvidia-tesla-t4".into()));
+        assert_eq!(res.get("gcp.gce.accelerator.count".into()), Some(2.into()));
+        assert_eq!(Resource::new(without_driver).get("gcp.gce.accelerator.type".into()), None);
+    }
+
+    #[test]
+    fn test_nvidia_driver_wins_over_machine_type() {
+        let root = FixtureDir::new("nvidia_gpus_a2");
+        root.write("proc/driver/nvidia/gpus/0000:00:04.0/information", "Model: \t\t NVIDIA A100-SXM4-80GB\n");
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "projects/233510669999/machineTypes/a2-highgpu-4g",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET", "CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+            block_on(GoogleCloudResourceDetector::builder().with_fs_root(&root.path).detect(&metadata))
+        };
+        let res = Resource::new(resources);
+        assert_eq!(res.get("gcp.gce.accelerator.type".into()), Some("nvidia-a100-sxm4-80gb".into()));
+        assert_eq!(res.get("gcp.gce.accelerator.count".into()), Some(1.into()));
+    }
+
+    #[test]
+    fn test_dataproc_resources_before_gce() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "spark-cluster-m",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {
+                    "dataproc-cluster-name": "spark-cluster",
+                    "dataproc-cluster-uuid": "4f3c5e1a-0d6b-4c43-9a34-7e4a8c0a2b11",
+                    "dataproc-role": "Master",
+                },
+            },
+        });
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET"]);
+
+            block_on(GoogleCloudResourceDetector::builder().detect(&metadata))
+        };
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.dataproc.cluster.name", "spark-cluster".to_string()),
+            KeyValue::new("gcp.dataproc.cluster.uuid", "4f3c5e1a-0d6b-4c43-9a34-7e4a8c0a2b11".to_string()),
+            KeyValue::new("gcp.dataproc.role", "master".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "spark-cluster-m".to_string()),
+            KeyValue::new("gcp.resource_type", "cloud_dataproc_cluster"),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "spark-cluster-m".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_get_dataflow_resources() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "wordcount-10151012-abcd-harness-x1b2",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {
+                    "job_id": "2024-10-15_01_12_33-123456789",
+                    "job_name": "wordcount",
+                    "worker_pool": "harness",
+                },
+            },
+        });
+        let resources = get_dataflow_resources(&DetectionContext::new(&metadata)).unwrap();
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.dataflow.job.id", "2024-10-15_01_12_33-123456789".to_string()),
+            KeyValue::new("gcp.dataflow.job.name", "wordcount".to_string()),
+            KeyValue::new("gcp.dataflow.worker_pool", "harness".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "wordcount-10151012-abcd-harness-x1b2".to_string()),
+            KeyValue::new("gcp.resource_type", "dataflow_job"),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "wordcount-10151012-abcd-harness-x1b2".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_get_gce_resources_zonal_mig() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "my-mig-x1b2",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {
+                    "created-by": "projects/233510669999/zones/us-east4-b/instanceGroupManagers/my-mig",
+                },
+            },
+        });
+        let resources = get_gce_resources(&DetectionContext::new(&metadata)).unwrap();
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.gce.instance_group_manager.name", "my-mig".to_string()),
+            KeyValue::new("gcp.gce.instance_group_manager.zone", "us-east4-b".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "my-mig-x1b2".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "my-mig-x1b2".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_get_gce_resources_regional_mig() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "my-mig-x1b2",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {
+                    "created-by": "projects/233510669999/regions/us-east4/instanceGroupManagers/my-mig",
+                },
+            },
+        });
+        let resources = get_gce_resources(&DetectionContext::new(&metadata)).unwrap();
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.gce.instance_group_manager.name", "my-mig".to_string()),
+            KeyValue::new("gcp.gce.instance_group_manager.region", "us-east4".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "my-mig-x1b2".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "my-mig-x1b2".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_get_gce_resources_not_managed() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "fakeProject"},
+            "instance": {
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {
+                    "created-by": "projects/233510669999/zones/us-east4-b/someOtherCreator/x",
+                },
+            },
+        });
+        let resources = get_gce_resources(&DetectionContext::new(&metadata)).unwrap();
+        assert!(!resources.iter().any(|kv| kv.key.as_str().starts_with("gcp.gce.instance_group_manager")));
+    }
+
+    #[test]
+    fn test_get_gke_resources_regional() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME","POD_NAME","HOSTNAME","NAMESPACE"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("HOSTNAME", "host_name");
+            TestEnvs::set_var("POD_NAME", "pod_name");
+
+            let metadata = serde_json::json!({
+                "instance": {
+                    "id": "instance_id",
+                    "zone": "projects/233510669999/zones/us-east4-b",
+                    "attributes": {
+                        "cluster-name": "cluster_name",
+                        "cluster-location": "us-east4",
+                    },
+                },
+                "project": {"projectId": "project_id"},
+            });
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "pod_name".to_string()),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("cloud.zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_get_gke_resources_zone() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME","POD_NAME","HOSTNAME","NAMESPACE"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("HOSTNAME", "host_name");
+            TestEnvs::set_var("POD_NAME", "pod_name");
+
+            let metadata = serde_json::json!({
+                "instance": {
+                    "id": "instance_id",
+                    "zone": "projects/233510669999/zones/us-east4-b",
+                    "attributes": {
+                        "cluster-name": "cluster_name",
+                        "cluster-location": "us-east4-b",
+                    },
+                },
+                "project": {"projectId": "project_id"},
+            });
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "pod_name".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.zone", "us-east4-b".to_string()),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+}
+
+// @patch_env
+// @mock.patch(
+//     "opentelemetry.resourcedetector.gcp_resource_detector.requests.get",
+//     **{"return_value.json.return_value": GKE_RESOURCES_JSON_STRING}
+// )
+// class TestGKEResourceFinder(unittest.TestCase):
+
+//     # pylint: disable=unused-argument
+//     def test_not_running_on_gke(self, getter):
+//         pop_environ_key(KUBERNETES_SERVICE_HOST)
+//         found_resources = get_gke_resources()
+//         self.assertEqual(found_resources, {})
+
+//     # pylint: disable=unused-argument
+//     def test_missing_container_name(self, getter):
+//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
+//         pop_environ_key(CONTAINER_NAME)
+//         found_resources = get_gke_resources()
+//         self.assertEqual(
+//             found_resources,
+//             {
+//                 "cloud.account.id": "project_id",
+//                 "k8s.cluster.name": "cluster_name",
+//                 "k8s.namespace.name": "",
+//                 "host.id": "instance_id",
+//                 "k8s.pod.name": "",
+//                 "cloud.zone": "zone",
+//                 "cloud.provider": "gcp",
+//                 "gcp.resource_type": "gke_container",
+//             },
+//         )
+
+//     # pylint: disable=unused-argument
+//     def test_environment_empty_strings(self, getter):
+//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
+//         os.environ[CONTAINER_NAME] = ""
+//         os.environ[NAMESPACE] = ""
+//         found_resources = get_gke_resources()
 //         self.assertEqual(
 //             found_resources,
 //             {
@@ -193,88 +924,783 @@ mod gce_gke_tests {
 //             },
 //         )
 
-//     def test_missing_namespace_file(self, getter):
-//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
-//         os.environ[CONTAINER_NAME] = "container_name"
-//         found_resources = get_gke_resources()
-//         self.assertEqual(
-//             found_resources,
-//             {
-//                 "cloud.account.id": "project_id",
-//                 "k8s.cluster.name": "cluster_name",
-//                 "k8s.namespace.name": "",
-//                 "host.id": "instance_id",
-//                 "k8s.pod.name": "",
-//                 "container.name": "container_name",
-//                 "cloud.zone": "zone",
-//                 "cloud.provider": "gcp",
-//                 "gcp.resource_type": "gke_container",
-//             },
-//         )
+//     def test_missing_namespace_file(self, getter):
+//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
+//         os.environ[CONTAINER_NAME] = "container_name"
+//         found_resources = get_gke_resources()
+//         self.assertEqual(
+//             found_resources,
+//             {
+//                 "cloud.account.id": "project_id",
+//                 "k8s.cluster.name": "cluster_name",
+//                 "k8s.namespace.name": "",
+//                 "host.id": "instance_id",
+//                 "k8s.pod.name": "",
+//                 "container.name": "container_name",
+//                 "cloud.zone": "zone",
+//                 "cloud.provider": "gcp",
+//                 "gcp.resource_type": "gke_container",
+//             },
+//         )
+
+//     def test_finding_gke_resources(self, getter):
+//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
+//         os.environ[NAMESPACE] = "namespace"
+//         os.environ[CONTAINER_NAME] = "container_name"
+//         os.environ[HOSTNAME] = "host_name"
+//         found_resources = get_gke_resources()
+//         self.assertEqual(getter.call_args_list[0][0][0], _GCP_METADATA_URL)
+//         self.assertEqual(
+//             found_resources,
+//             {
+//                 "cloud.account.id": "project_id",
+//                 "k8s.cluster.name": "cluster_name",
+//                 "k8s.namespace.name": "namespace",
+//                 "host.id": "instance_id",
+//                 "k8s.pod.name": "host_name",
+//                 "container.name": "container_name",
+//                 "cloud.zone": "zone",
+//                 "cloud.provider": "gcp",
+//                 "gcp.resource_type": "gke_container",
+//             },
+//         )
+
+//     def test_finding_gke_resources_with_pod_name(self, getter):
+//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
+//         os.environ[NAMESPACE] = "namespace"
+//         os.environ[CONTAINER_NAME] = "container_name"
+//         os.environ[HOSTNAME] = "host_name"
+//         os.environ[POD_NAME] = "pod_name"
+//         found_resources = get_gke_resources()
+//         self.assertEqual(getter.call_args_list[0][0][0], _GCP_METADATA_URL)
+//         self.assertEqual(
+//             found_resources,
+//             {
+//                 "cloud.account.id": "project_id",
+//                 "k8s.cluster.name": "cluster_name",
+//                 "k8s.namespace.name": "namespace",
+//                 "host.id": "instance_id",
+//                 "k8s.pod.name": "pod_name",
+//                 "container.name": "container_name",
+//                 "cloud.zone": "zone",
+//                 "cloud.provider": "gcp",
+//                 "gcp.resource_type": "gke_container",
+//             },
+//         )
+
+#[cfg(test)]
+mod gke_tests {
+
+    use std::{fs, io::Write, path::Path};
+
+    use super::*;
+    use once_cell::sync::Lazy;
+    // use pretty_assertions::{assert_eq, assert_ne};
+    use pretty_assertions_sorted::{assert_eq, assert_eq_sorted};
+    use regex::Regex;
+    use crate::{get_attached_k8s_resources, get_gke_resources, test_envs::TestEnvs, GoogleCloudResourceDetector};
+    use crate::k8s_workload::K8sApiConfig;
+    use crate::test_k8s_workload::{block_on, owned_by, FakeApiServer};
+    
+    static GKE_RESOURCES_JSON_STRING: Lazy<serde_json::Value> = Lazy::new(|| {
+        serde_json::json!({
+            "instance": {
+                "id": "instance_id",
+                "zone": "projects/123/zones/zone",
+                "attributes": {"cluster-name": "cluster_name"},
+            },
+            "project": {"projectId": "project_id"},
+        })
+    });
+
+    #[test]
+    fn test_not_running_on_cloudrun() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let metadata = serde_json::json!({});
+        TestEnvs::remove_var("KUBERNETES_SERVICE_HOST");
+        let resources = get_gke_resources(&DetectionContext::new(&metadata));
+        assert_eq!(resources.is_err(), true);
+    }
+
+    #[test]
+    fn test_missing_container_name() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::remove_var("CONTAINER_NAME");
+
+            let metadata = GKE_RESOURCES_JSON_STRING.clone();
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_environment_empty_strings() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("CONTAINER_NAME", "");
+            TestEnvs::set_var("NAMESPACE", "");
+
+            let metadata = GKE_RESOURCES_JSON_STRING.clone();
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "".to_string()),
+            KeyValue::new("container.name", "".to_string()),
+            KeyValue::new("k8s.container.name", "".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_best_effort_gke_resources_without_cluster_name() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME", "NODE_NAME", "K_SERVICE", "K_REVISION"]);
+        TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+        TestEnvs::set_var("POD_NAME", "pod_name");
+
+        let mut metadata = GKE_RESOURCES_JSON_STRING.clone();
+        metadata["instance"]["attributes"] = serde_json::json!({});
+        let strict = get_gke_resources(&DetectionContext::new(&metadata));
+        assert_eq!(strict.unwrap_err().to_string(), "instance.attributes.cluster-name not found");
+
+        let context = DetectionContext::new(&metadata).with_best_effort(true);
+        let res = Resource::new(get_gke_resources(&context).unwrap());
+        assert_eq!(res.get("k8s.cluster.name".into()), None);
+        assert_eq!(res.get("cloud.platform".into()), Some("gcp_kubernetes_engine".into()));
+        assert_eq!(context.take_missing(), vec!["instance.attributes.cluster-name".to_string()]);
+    }
+
+    #[test]
+    fn test_missing_namespace_file() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("CONTAINER_NAME", "container_name");
+
+            let metadata = GKE_RESOURCES_JSON_STRING.clone();
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "".to_string()),
+            KeyValue::new("container.name", "container_name".to_string()),
+            KeyValue::new("k8s.container.name", "container_name".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_exist_namespace_file() {
+        // sudo cargo test -- test_detector::gke_tests::test_exist_namespace_file
+        let dir_path = Path::new("/var/run/secrets/kubernetes.io/serviceaccount");
+        let file_path = dir_path.join("namespace");
+
+        fs::create_dir_all(dir_path).unwrap();
+    
+        // Create and write to the file
+        let mut file = fs::File::create(file_path).unwrap();
+        file.write_all(b"namespace").unwrap();
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("CONTAINER_NAME", "container_name");
+
+            let metadata = GKE_RESOURCES_JSON_STRING.clone();
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        fs::remove_file("/var/run/secrets/kubernetes.io/serviceaccount/namespace").unwrap();
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "".to_string()),
+            KeyValue::new("container.name", "container_name".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_finding_gke_resources() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE", "HOSTNAME"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("CONTAINER_NAME", "container_name");
+            TestEnvs::set_var("HOSTNAME", "host_name");
+
+            let metadata = GKE_RESOURCES_JSON_STRING.clone();
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "host_name".to_string()),
+            KeyValue::new("container.name", "container_name".to_string()),
+            KeyValue::new("k8s.container.name", "container_name".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+        assert_eq!(crate::mapping::get_monitored_resource(res).unwrap().r#type, "k8s_container");
+    }
+
+    #[test]
+    fn test_finding_gke_resources_with_pod_name() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("CONTAINER_NAME", "container_name");
+            TestEnvs::set_var("HOSTNAME", "host_name");
+            TestEnvs::set_var("POD_NAME", "pod_name");
+
+            let metadata = GKE_RESOURCES_JSON_STRING.clone();
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "pod_name".to_string()),
+            KeyValue::new("container.name", "container_name".to_string()),
+            KeyValue::new("k8s.container.name", "container_name".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_finding_gke_resources_with_knative() {
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME", "K_SERVICE", "K_REVISION", "K_CONFIGURATION"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("CONTAINER_NAME", "user-container");
+            TestEnvs::set_var("POD_NAME", "hello-00001-deployment-5f8d9b7c6d-q8z2x");
+            TestEnvs::set_var("K_SERVICE", "hello");
+            TestEnvs::set_var("K_REVISION", "hello-00001");
+            TestEnvs::set_var("K_CONFIGURATION", "hello");
+
+            let metadata = GKE_RESOURCES_JSON_STRING.clone();
+            get_gke_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res = Resource::new(resources);
+
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "hello-00001-deployment-5f8d9b7c6d-q8z2x".to_string()),
+            KeyValue::new("container.name", "user-container".to_string()),
+            KeyValue::new("k8s.container.name", "user-container".to_string()),
+            KeyValue::new("faas.name", "hello".to_string()),
+            KeyValue::new("faas.version", "hello-00001".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_finding_self_managed_k8s_resources() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "project_id"},
+            "instance": {
+                "name": "worker-1",
+                "id": "instance_id",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        let (resources, disabled) = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME", "NODE_NAME", "K8S_CLUSTER_NAME"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("CONTAINER_NAME", "container_name");
+            TestEnvs::set_var("POD_NAME", "pod_name");
+            TestEnvs::set_var("NODE_NAME", "worker-1");
+            TestEnvs::set_var("K8S_CLUSTER_NAME", "from_env");
+
+            let builder = GoogleCloudResourceDetector::builder()
+                .with_self_managed_k8s(true)
+                .with_k8s_cluster_name("kubeadm-cluster")
+                .with_fs_root(std::env::temp_dir().join("otel_gcp_empty_root"));
+            let disabled = GoogleCloudResourceDetector::builder();
+            (block_on(builder.detect(&metadata)), block_on(disabled.detect(&metadata)))
+        };
+        let res = Resource::new(resources);
+
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("container.name", "container_name".to_string()),
+            KeyValue::new("gcp.gce.instance.name", "worker-1".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("host.name", "worker-1".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
+            KeyValue::new("k8s.cluster.name", "kubeadm-cluster".to_string()),
+            KeyValue::new("k8s.container.name", "container_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
+            KeyValue::new("k8s.node.name", "worker-1".to_string()),
+            KeyValue::new("k8s.pod.name", "pod_name".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+        assert_eq!(Resource::new(disabled).get("k8s.pod.name".into()), None);
+        // Same monitored resource kind as GKE
+        assert_eq!(crate::mapping::get_monitored_resource(res).unwrap().r#type, "k8s_container");
+    }
+
+    #[test]
+    fn test_finding_attached_k8s_resources_without_metadata() {
+        let fixture = crate::test_fixtures::FixtureDir::new("fleet_membership");
+        let membership_path = fixture.write("membership", "projects/my-project/locations/us-east1/memberships/onprem-cluster\n");
+        let (resources, from_env) = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME", "NODE_NAME", "K8S_CLUSTER_NAME", "FLEET_MEMBERSHIP", "GOOGLE_CLOUD_PROJECT", "CLUSTER_LOCATION", "BUILD_ID", "K_SERVICE", "K_REVISION"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("CONTAINER_NAME", "container_name");
+            TestEnvs::set_var("POD_NAME", "pod_name");
+
+            let disabled = GoogleCloudResourceDetector::builder().with_fleet_membership_path(&membership_path);
+            assert!(block_on(disabled.detect(&serde_json::Value::Null)).is_empty());
+
+            let builder = GoogleCloudResourceDetector::builder()
+                .with_attached_k8s(true)
+                .with_fleet_membership_path(&membership_path)
+                .with_fs_root(std::env::temp_dir().join("otel_gcp_empty_root"));
+            let resources = block_on(builder.detect(&serde_json::Value::Null));
+
+            TestEnvs::set_var("FLEET_MEMBERSHIP", "projects/123456789/locations/global/memberships/attached-eks");
+            TestEnvs::set_var("GOOGLE_CLOUD_PROJECT", "env-project");
+            TestEnvs::set_var("CLUSTER_LOCATION", "us-west2-a");
+            TestEnvs::set_var("K8S_CLUSTER_NAME", "eks-prod");
+            let builder = GoogleCloudResourceDetector::builder()
+                .with_attached_k8s(true)
+                .with_fleet_membership_path(&membership_path)
+                .with_fs_root(std::env::temp_dir().join("otel_gcp_empty_root"));
+            (resources, block_on(builder.detect(&serde_json::Value::Null)))
+        };
 
-//     def test_finding_gke_resources(self, getter):
-//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
-//         os.environ[NAMESPACE] = "namespace"
-//         os.environ[CONTAINER_NAME] = "container_name"
-//         os.environ[HOSTNAME] = "host_name"
-//         found_resources = get_gke_resources()
-//         self.assertEqual(getter.call_args_list[0][0][0], _GCP_METADATA_URL)
-//         self.assertEqual(
-//             found_resources,
-//             {
-//                 "cloud.account.id": "project_id",
-//                 "k8s.cluster.name": "cluster_name",
-//                 "k8s.namespace.name": "namespace",
-//                 "host.id": "instance_id",
-//                 "k8s.pod.name": "host_name",
-//                 "container.name": "container_name",
-//                 "cloud.zone": "zone",
-//                 "cloud.provider": "gcp",
-//                 "gcp.resource_type": "gke_container",
-//             },
-//         )
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "my-project"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east1"),
+            KeyValue::new("container.name", "container_name"),
+            KeyValue::new("gcp.fleet.membership.name", "projects/my-project/locations/us-east1/memberships/onprem-cluster"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("k8s.cluster.name", "onprem-cluster"),
+            KeyValue::new("k8s.container.name", "container_name"),
+            KeyValue::new("k8s.namespace.name", "namespace"),
+            KeyValue::new("k8s.pod.name", "pod_name"),
+        ]);
+        assert_eq_sorted!(Resource::new(resources), res_sould_be);
 
-//     def test_finding_gke_resources_with_pod_name(self, getter):
-//         os.environ[KUBERNETES_SERVICE_HOST] = "10.0.0.1"
-//         os.environ[NAMESPACE] = "namespace"
-//         os.environ[CONTAINER_NAME] = "container_name"
-//         os.environ[HOSTNAME] = "host_name"
-//         os.environ[POD_NAME] = "pod_name"
-//         found_resources = get_gke_resources()
-//         self.assertEqual(getter.call_args_list[0][0][0], _GCP_METADATA_URL)
-//         self.assertEqual(
-//             found_resources,
-//             {
-//                 "cloud.account.id": "project_id",
-//                 "k8s.cluster.name": "cluster_name",
-//                 "k8s.namespace.name": "namespace",
-//                 "host.id": "instance_id",
-//                 "k8s.pod.name": "pod_name",
-//                 "container.name": "container_name",
-//                 "cloud.zone": "zone",
-//                 "cloud.provider": "gcp",
-//                 "gcp.resource_type": "gke_container",
-//             },
-//         )
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "env-project"),
+            KeyValue::new("cloud.availability_zone", "us-west2-a"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("container.name", "container_name"),
+            KeyValue::new("gcp.fleet.membership.name", "projects/123456789/locations/global/memberships/attached-eks"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("k8s.cluster.name", "eks-prod"),
+            KeyValue::new("k8s.container.name", "container_name"),
+            KeyValue::new("k8s.namespace.name", "namespace"),
+            KeyValue::new("k8s.pod.name", "pod_name"),
+        ]);
+        assert_eq_sorted!(Resource::new(from_env), res_sould_be);
+    }
+
+    #[test]
+    fn test_attached_k8s_without_project() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K8S_CLUSTER_NAME", "FLEET_MEMBERSHIP", "GOOGLE_CLOUD_PROJECT", "BUILD_ID"]);
+
+        TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+        TestEnvs::set_var("K8S_CLUSTER_NAME", "eks-prod");
+        let builder = GoogleCloudResourceDetector::builder().with_attached_k8s(true);
+        assert!(block_on(builder.detect(&serde_json::Value::Null)).is_empty());
+    }
+
+    #[test]
+    fn test_attached_k8s_project_number_membership() {
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K8S_CLUSTER_NAME", "FLEET_MEMBERSHIP", "GOOGLE_CLOUD_PROJECT", "CLUSTER_LOCATION", "BUILD_ID"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("FLEET_MEMBERSHIP", "projects/123456789/locations/global/memberships/attached-eks");
+            get_attached_k8s_resources(&DetectionContext::new(&serde_json::Value::Null), None, None).unwrap()
+        };
+        let res = Resource::new(resources);
+        assert_eq!(res.get("cloud.account.id".into()), Some("123456789".into()));
+        assert_eq!(res.get("gcp.project.number".into()), Some("123456789".into()));
+        assert_eq!(res.get("k8s.cluster.name".into()), Some("attached-eks".into()));
+    }
+
+    #[test]
+    fn test_simulated_gke_resources_from_env() {
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME", "K_SERVICE", "K_REVISION", "OTEL_GCP_PLATFORM", "OTEL_GCP_PROJECT_ID", "OTEL_GCP_PROJECT_NUMBER", "OTEL_GCP_REGION", "OTEL_GCP_ZONE", "OTEL_GCP_INSTANCE_ID", "OTEL_GCP_CLUSTER_NAME", "GOOGLE_CLOUD_PROJECT"]);
+
+            TestEnvs::set_var("OTEL_GCP_PLATFORM", "gke");
+            TestEnvs::set_var("GOOGLE_CLOUD_PROJECT", "my-project");
+            TestEnvs::set_var("OTEL_GCP_ZONE", "us-central1-f");
+            TestEnvs::set_var("OTEL_GCP_CLUSTER_NAME", "local-cluster");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("POD_NAME", "pod_name");
+
+            block_on(GoogleCloudResourceDetector::builder().build()).get_resource()
+        };
+
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "my-project"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-central1"),
+            KeyValue::new("cloud.zone", "us-central1-f"),
+            KeyValue::new("gcp.project.number", "000000000000"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("gcp.simulated", true),
+            KeyValue::new("host.id", "0000000000000000000"),
+            KeyValue::new("k8s.cluster.name", "local-cluster"),
+            KeyValue::new("k8s.namespace.name", "namespace"),
+            KeyValue::new("k8s.pod.name", "pod_name"),
+        ]);
+        assert_eq_sorted!(resources, res_sould_be);
+    }
+
+    #[test]
+    fn test_finding_gke_resources_with_workload() {
+        let server = FakeApiServer::start(None, vec![
+            ("/api/v1/namespaces/namespace/pods/web-0", owned_by("StatefulSet", "web")),
+        ]);
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("POD_NAME", "web-0");
+
+            let metadata = GKE_RESOURCES_JSON_STRING.clone();
+            let builder = GoogleCloudResourceDetector::builder()
+                .with_k8s_api(K8sApiConfig::new(&server.url))
+                .with_fs_root(std::env::temp_dir().join("otel_gcp_empty_root"));
+            block_on(builder.detect(&metadata))
+        };
+        let res = Resource::new(resources);
+
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
+            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("k8s.pod.name", "web-0".to_string()),
+            KeyValue::new("k8s.statefulset.name", "web".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
+            KeyValue::new("gcp.resource_type", "gke_container"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+}
+
+#[cfg(test)]
+mod cloudfunctions_tests {
+
+    use super::*;
+    use once_cell::sync::Lazy;
+    // use pretty_assertions::{assert_eq, assert_ne};
+    use pretty_assertions_sorted::{assert_eq, assert_eq_sorted};
+    use regex::Regex;
+    use crate::{get_cloudfunctions_resources, get_cloudrun_resources, test_envs::TestEnvs, GoogleCloudResourceDetector};
+    use crate::test_k8s_workload::block_on;
+    
+    static CLOUDFUNCTIONS_RESOURCES_JSON_STRING: Lazy<serde_json::Value> = Lazy::new(|| {
+        serde_json::json!({
+            "instance": {
+                "id": "instance_id",
+                "zone": "projects/123/zones/zone",
+                "region": "projects/123/regions/region",
+            },
+            "project": {"projectId": "project_id"},
+        })
+    });
+
+    #[test]
+    fn test_cloudfunctions_resources_without_metadata_server() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(vec!["FUNCTION_TARGET", "K_CONFIGURATION", "K_SERVICE", "K_REVISION", "KUBERNETES_SERVICE_HOST", "AIP_DEPLOYED_MODEL_ID", "CLOUD_ML_JOB_ID", "CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+
+        TestEnvs::set_var("FUNCTION_TARGET", "function_target");
+        TestEnvs::set_var("K_SERVICE", "function");
+        TestEnvs::set_var("K_REVISION", "function-00001");
+
+        // The detector loop switches to best-effort mode, the platform is identified by the environment
+        let builder = GoogleCloudResourceDetector::builder().with_fs_root(std::env::temp_dir().join("otel_gcp_empty_root"));
+        let (resources, report) = block_on(builder.detect_with_report(&serde_json::Value::Null));
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.platform", "gcp_cloud_functions"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("faas.name", "function"),
+            KeyValue::new("faas.version", "function-00001"),
+            KeyValue::new("gcp.resource_type", "cloud_functions"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+        assert_eq!(report.platform(), Some("cloud_functions"));
+        assert_eq!(report.attempts.last().unwrap().missing, ["project.projectId", "instance.region", "instance.zone", "instance.id"].map(String::from).to_vec());
+
+        // Called directly or with metadata, missing attributes still reject the platform
+        assert!(get_cloudfunctions_resources(&DetectionContext::new(&serde_json::Value::Null)).is_err());
+        let metadata = serde_json::json!({"project": {"projectId": "project_id"}});
+        assert!(get_cloudfunctions_resources(&DetectionContext::new(&metadata)).is_err());
+    }
+
+    #[test]
+    fn test_not_running_on_cloudrun() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let metadata = serde_json::json!({});
+        let resources = get_cloudrun_resources(&DetectionContext::new(&metadata));
+        assert_eq!(resources.is_err(), true);
+    }
+
+    #[test]
+    fn test_missing_service_name() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["FUNCTION_TARGET","K_SERVICE","K_REVISION"]);
+
+            TestEnvs::set_var("FUNCTION_TARGET", "function");
+            TestEnvs::remove_var("K_SERVICE");
+            TestEnvs::remove_var("K_REVISION");
+
+            let metadata = CLOUDFUNCTIONS_RESOURCES_JSON_STRING.clone();
+            get_cloudfunctions_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_functions"),
+            KeyValue::new("cloud.region", "region".to_string()),
+            KeyValue::new("faas.instance", "instance_id".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("gcp.resource_type", "cloud_functions"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_environment_empty_strings() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["FUNCTION_TARGET","K_SERVICE","K_REVISION"]);
+
+            TestEnvs::set_var("FUNCTION_TARGET", "function");
+            TestEnvs::set_var("K_SERVICE","");
+            TestEnvs::set_var("K_REVISION","");
+
+            let metadata = CLOUDFUNCTIONS_RESOURCES_JSON_STRING.clone();
+            get_cloudfunctions_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_functions"),
+            KeyValue::new("cloud.region", "region".to_string()),
+            KeyValue::new("faas.instance", "instance_id".to_string()),
+            KeyValue::new("faas.name", "".to_string()),
+            KeyValue::new("faas.version", "".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("gcp.resource_type", "cloud_functions"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_finding_cloudfunctions_resources() {
+        let resources = {  
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["FUNCTION_TARGET","K_SERVICE","K_REVISION"]);
+
+            TestEnvs::set_var("FUNCTION_TARGET", "function");
+            TestEnvs::set_var("K_SERVICE","service");
+            TestEnvs::set_var("K_REVISION","revision");
+
+            let metadata = CLOUDFUNCTIONS_RESOURCES_JSON_STRING.clone();
+            get_cloudfunctions_resources(&DetectionContext::new(&metadata)).unwrap()
+        };
+        let res_default = Resource::default();
+        let res = Resource::new(resources);
+        // let res = res.merge(&res_default);
+        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
+        
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_functions"),
+            KeyValue::new("cloud.region", "region".to_string()),
+            KeyValue::new("faas.instance", "instance_id".to_string()),
+            KeyValue::new("faas.name", "service".to_string()),
+            KeyValue::new("faas.version", "revision".to_string()),
+            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("gcp.resource_type", "cloud_functions"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
 
-#[cfg(test)]
-mod gke_tests {
+    #[test]
+    fn test_finding_cloudfunctions_resources_with_service_account() {
+        let mut metadata = CLOUDFUNCTIONS_RESOURCES_JSON_STRING.clone();
+        metadata["instance"]["service-accounts"] = serde_json::json!({
+            "default": {"email": "fn-runner@project_id.iam.gserviceaccount.com"},
+        });
+        let (enabled, disabled) = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","K_CONFIGURATION","FUNCTION_TARGET","K_SERVICE","K_REVISION"]);
 
-    use std::{fs, io::Write, path::Path};
+            TestEnvs::set_var("FUNCTION_TARGET", "function");
+
+            let enabled = GoogleCloudResourceDetector::builder().with_service_account(true);
+            let disabled = GoogleCloudResourceDetector::builder();
+            (block_on(enabled.detect(&metadata)), block_on(disabled.detect(&metadata)))
+        };
+        let email = Resource::new(enabled).get("gcp.service_account.email".into());
+        assert_eq!(email, Some("fn-runner@project_id.iam.gserviceaccount.com".into()));
+        assert_eq!(Resource::new(disabled).get("gcp.service_account.email".into()), None);
+    }
+
+}
+
+#[cfg(test)]
+mod cloudrun_tests {
 
     use super::*;
     use once_cell::sync::Lazy;
     // use pretty_assertions::{assert_eq, assert_ne};
     use pretty_assertions_sorted::{assert_eq, assert_eq_sorted};
     use regex::Regex;
-    use crate::{get_gke_resources, test_envs::TestEnvs};
-    
-    static GKE_RESOURCES_JSON_STRING: Lazy<serde_json::Value> = Lazy::new(|| {
+    use crate::{get_cloudrun_resources, simulation::SimulatedPlatform, test_envs::TestEnvs, GoogleCloudResourceDetector};
+    use crate::test_fixtures::FixtureDir;
+    use crate::test_k8s_workload::block_on;
+    static CLOUDRUN_RESOURCES_JSON_STRING: Lazy<serde_json::Value> = Lazy::new(|| {
         serde_json::json!({
             "instance": {
                 "id": "instance_id",
                 "zone": "projects/123/zones/zone",
-                "attributes": {"cluster-name": "cluster_name"},
+                "region": "projects/123/regions/region",
             },
             "project": {"projectId": "project_id"},
         })
@@ -284,22 +1710,22 @@ mod gke_tests {
     fn test_not_running_on_cloudrun() {
         let _m = THE_RESOURCE.lock().unwrap();
         let metadata = serde_json::json!({});
-        TestEnvs::remove_var("KUBERNETES_SERVICE_HOST");
-        let resources = get_gke_resources(&metadata);
+        let resources = get_cloudrun_resources(&DetectionContext::new(&metadata));
         assert_eq!(resources.is_err(), true);
     }
 
     #[test]
-    fn test_missing_container_name() {
+    fn test_missing_service_name() {
         let resources = {  
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME"]);
+            let _e = TestEnvs::new(vec!["K_CONFIGURATION","K_SERVICE","K_REVISION"]);
 
-            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
-            TestEnvs::remove_var("CONTAINER_NAME");
+            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
+            TestEnvs::remove_var("K_SERVICE");
+            TestEnvs::remove_var("K_REVISION");
 
-            let metadata = GKE_RESOURCES_JSON_STRING.clone();
-            get_gke_resources(&metadata).unwrap()
+            let metadata = CLOUDRUN_RESOURCES_JSON_STRING.clone();
+            get_cloudrun_resources(&DetectionContext::new(&metadata)).unwrap()
         };
         let res_default = Resource::default();
         let res = Resource::new(resources);
@@ -308,30 +1734,29 @@ mod gke_tests {
         
         let res_sould_be = Resource::new(vec![
             KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
-            KeyValue::new("k8s.namespace.name", "".to_string()),
-            KeyValue::new("host.id", "instance_id".to_string()),
-            KeyValue::new("k8s.pod.name", "".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_run"),
+            KeyValue::new("cloud.region", "region".to_string()),
+            KeyValue::new("faas.instance", "instance_id".to_string()),
             KeyValue::new("cloud.zone", "zone".to_string()),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
-            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("gcp.resource_type", "cloud_run"),
         ]);
         assert_eq_sorted!(res, res_sould_be);
+        
     }
 
     #[test]
     fn test_environment_empty_strings() {
         let resources = {  
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE"]);
+            let _e = TestEnvs::new(vec!["K_CONFIGURATION","K_SERVICE","K_REVISION"]);
 
-            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
-            TestEnvs::set_var("CONTAINER_NAME", "");
-            TestEnvs::set_var("NAMESPACE", "");
+            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
+            TestEnvs::set_var("K_SERVICE", "");
+            TestEnvs::set_var("K_REVISION", "");
 
-            let metadata = GKE_RESOURCES_JSON_STRING.clone();
-            get_gke_resources(&metadata).unwrap()
+            let metadata = CLOUDRUN_RESOURCES_JSON_STRING.clone();
+            get_cloudrun_resources(&DetectionContext::new(&metadata)).unwrap()
         };
         let res_default = Resource::default();
         let res = Resource::new(resources);
@@ -340,30 +1765,31 @@ mod gke_tests {
         
         let res_sould_be = Resource::new(vec![
             KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
-            KeyValue::new("k8s.namespace.name", "".to_string()),
-            KeyValue::new("host.id", "instance_id".to_string()),
-            KeyValue::new("k8s.pod.name", "".to_string()),
-            KeyValue::new("container.name", "".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_run"),
+            KeyValue::new("cloud.region", "region".to_string()),
+            KeyValue::new("faas.instance", "instance_id".to_string()),
+            KeyValue::new("faas.name", "".to_string()),
+            KeyValue::new("faas.version", "".to_string()),
             KeyValue::new("cloud.zone", "zone".to_string()),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
-            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("gcp.resource_type", "cloud_run"),
         ]);
         assert_eq_sorted!(res, res_sould_be);
+        
     }
 
     #[test]
-    fn test_missing_namespace_file() {
+    fn test_finding_cloudrun_resources() {
         let resources = {  
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE"]);
+            let _e = TestEnvs::new(vec!["K_CONFIGURATION","K_SERVICE","K_REVISION"]);
 
-            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
-            TestEnvs::set_var("CONTAINER_NAME", "container_name");
+            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
+            TestEnvs::set_var("K_SERVICE", "service");
+            TestEnvs::set_var("K_REVISION", "revision");
 
-            let metadata = GKE_RESOURCES_JSON_STRING.clone();
-            get_gke_resources(&metadata).unwrap()
+            let metadata = CLOUDRUN_RESOURCES_JSON_STRING.clone();
+            get_cloudrun_resources(&DetectionContext::new(&metadata)).unwrap()
         };
         let res_default = Resource::default();
         let res = Resource::new(resources);
@@ -372,381 +1798,751 @@ mod gke_tests {
         
         let res_sould_be = Resource::new(vec![
             KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
-            KeyValue::new("k8s.namespace.name", "".to_string()),
-            KeyValue::new("host.id", "instance_id".to_string()),
-            KeyValue::new("k8s.pod.name", "".to_string()),
-            KeyValue::new("container.name", "container_name".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_run"),
+            KeyValue::new("cloud.region", "region".to_string()),
+            KeyValue::new("faas.instance", "instance_id".to_string()),
+            KeyValue::new("faas.name", "service".to_string()),
+            KeyValue::new("faas.version", "revision".to_string()),
             KeyValue::new("cloud.zone", "zone".to_string()),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
-            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("gcp.resource_type", "cloud_run"),
         ]);
         assert_eq_sorted!(res, res_sould_be);
+        
     }
 
     #[test]
-    #[ignore]
-    fn test_exist_namespace_file() {
-        // sudo cargo test -- test_detector::gke_tests::test_exist_namespace_file
-        let dir_path = Path::new("/var/run/secrets/kubernetes.io/serviceaccount");
-        let file_path = dir_path.join("namespace");
-
-        fs::create_dir_all(dir_path).unwrap();
-    
-        // Create and write to the file
-        let mut file = fs::File::create(file_path).unwrap();
-        file.write_all(b"namespace").unwrap();
+    fn test_finding_cloudrun_resources_with_container_id() {
+        let container_id = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        let root = FixtureDir::new("cloudrun_root");
+        root.write("proc/self/cgroup", &format!("0::/system.slice/docker-{}.scope\n", container_id));
         let resources = {  
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE"]);
+            let _e = TestEnvs::new(vec!["K_CONFIGURATION","K_SERVICE","K_REVISION","KUBERNETES_SERVICE_HOST"]);
 
-            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
-            TestEnvs::set_var("CONTAINER_NAME", "container_name");
+            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
+            TestEnvs::set_var("K_SERVICE", "service");
+            TestEnvs::set_var("K_REVISION", "revision");
 
-            let metadata = GKE_RESOURCES_JSON_STRING.clone();
-            get_gke_resources(&metadata).unwrap()
+            let metadata = CLOUDRUN_RESOURCES_JSON_STRING.clone();
+            let builder = GoogleCloudResourceDetector::builder().with_fs_root(&root.path);
+            block_on(builder.detect(&metadata))
         };
-        fs::remove_file("/var/run/secrets/kubernetes.io/serviceaccount/namespace").unwrap();
-        let res_default = Resource::default();
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
         
         let res_sould_be = Resource::new(vec![
             KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
-            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
-            KeyValue::new("host.id", "instance_id".to_string()),
-            KeyValue::new("k8s.pod.name", "".to_string()),
-            KeyValue::new("container.name", "container_name".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_run"),
+            KeyValue::new("cloud.region", "region".to_string()),
+            KeyValue::new("container.id", container_id.to_string()),
+            KeyValue::new("faas.instance", "instance_id".to_string()),
+            KeyValue::new("faas.name", "service".to_string()),
+            KeyValue::new("faas.version", "revision".to_string()),
             KeyValue::new("cloud.zone", "zone".to_string()),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
-            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("gcp.resource_type", "cloud_run"),
         ]);
         assert_eq_sorted!(res, res_sould_be);
     }
 
     #[test]
-    fn test_finding_gke_resources() {
-        let resources = {  
+    fn test_simulated_cloudrun_resources() {
+        let resources = {
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE", "HOSTNAME"]);
+            let _e = TestEnvs::new(vec!["K_CONFIGURATION", "K_SERVICE", "K_REVISION", "OTEL_GCP_PLATFORM", "OTEL_GCP_PROJECT_ID", "OTEL_GCP_PROJECT_NUMBER", "OTEL_GCP_REGION", "OTEL_GCP_ZONE", "OTEL_GCP_INSTANCE_ID", "GOOGLE_CLOUD_PROJECT"]);
 
-            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
-            TestEnvs::set_var("NAMESPACE", "namespace");
-            TestEnvs::set_var("CONTAINER_NAME", "container_name");
-            TestEnvs::set_var("HOSTNAME", "host_name");
+            TestEnvs::set_var("K_SERVICE", "service");
+            TestEnvs::set_var("K_REVISION", "revision");
+            TestEnvs::set_var("OTEL_GCP_PROJECT_ID", "my-project");
+            TestEnvs::set_var("OTEL_GCP_PROJECT_NUMBER", "123456789");
+            TestEnvs::set_var("OTEL_GCP_REGION", "europe-west1");
+            TestEnvs::set_var("OTEL_GCP_INSTANCE_ID", "local-instance");
 
-            let metadata = GKE_RESOURCES_JSON_STRING.clone();
-            get_gke_resources(&metadata).unwrap()
+            let builder = GoogleCloudResourceDetector::builder().with_simulated_platform(SimulatedPlatform::CloudRun);
+            block_on(builder.build()).get_resource()
+        };
+
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "my-project"),
+            KeyValue::new("cloud.platform", "gcp_cloud_run"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "europe-west1"),
+            KeyValue::new("cloud.zone", "europe-west1-a"),
+            KeyValue::new("faas.instance", "local-instance"),
+            KeyValue::new("faas.name", "service"),
+            KeyValue::new("faas.version", "revision"),
+            KeyValue::new("gcp.project.number", "123456789"),
+            KeyValue::new("gcp.resource_type", "cloud_run"),
+            KeyValue::new("gcp.simulated", true),
+        ]);
+        assert_eq_sorted!(resources, res_sould_be);
+    }
+
+    #[test]
+    fn test_best_effort_cloudrun_resources_without_zone() {
+        let metadata = serde_json::json!({
+            "instance": {"id": "instance_id", "region": "projects/123/regions/region"},
+            "project": {"projectId": "project_id"},
+        });
+        let (strict, (resources, report)) = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["K_CONFIGURATION", "K_SERVICE", "K_REVISION", "KUBERNETES_SERVICE_HOST", "AIP_DEPLOYED_MODEL_ID", "CLOUD_ML_JOB_ID", "CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+
+            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
+            TestEnvs::set_var("K_SERVICE", "service");
+            TestEnvs::set_var("K_REVISION", "revision");
+
+            let root = std::env::temp_dir().join("otel_gcp_empty_root");
+            let strict = block_on(GoogleCloudResourceDetector::builder().with_fs_root(&root).detect(&metadata));
+            let builder = GoogleCloudResourceDetector::builder().with_fs_root(&root).with_best_effort(true);
+            (strict, block_on(builder.detect_with_report(&metadata)))
+        };
+        assert!(strict.is_empty());
+
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "project_id"),
+            KeyValue::new("cloud.platform", "gcp_cloud_run"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "region"),
+            KeyValue::new("faas.instance", "instance_id"),
+            KeyValue::new("faas.name", "service"),
+            KeyValue::new("faas.version", "revision"),
+            KeyValue::new("gcp.resource_type", "cloud_run"),
+        ]);
+        assert_eq_sorted!(Resource::new(resources), res_sould_be);
+        assert_eq!(report.platform(), Some("cloud_run"));
+        assert_eq!(report.attempts.last().unwrap().missing, vec!["instance.zone".to_string()]);
+    }
+
+    #[test]
+    fn test_cloudrun_resources_without_metadata_server() {
+        let (resources, report) = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["K_CONFIGURATION", "K_SERVICE", "K_REVISION", "KUBERNETES_SERVICE_HOST", "AIP_DEPLOYED_MODEL_ID", "CLOUD_ML_JOB_ID", "CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+
+            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
+            TestEnvs::set_var("K_SERVICE", "service");
+            TestEnvs::set_var("K_REVISION", "revision");
+
+            let builder = GoogleCloudResourceDetector::builder().with_fs_root(std::env::temp_dir().join("otel_gcp_empty_root"));
+            block_on(builder.detect_with_report(&serde_json::Value::Null))
+        };
+
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.platform", "gcp_cloud_run"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("faas.name", "service"),
+            KeyValue::new("faas.version", "revision"),
+            KeyValue::new("gcp.resource_type", "cloud_run"),
+        ]);
+        assert_eq_sorted!(Resource::new(resources), res_sould_be);
+        assert_eq!(report.platform(), Some("cloud_run"));
+        let missing = ["project.projectId", "instance.region", "instance.zone", "instance.id"].map(String::from).to_vec();
+        assert_eq!(report.attempts.last().unwrap().missing, missing);
+    }
+}
+
+#[cfg(test)]
+mod batch_build_tests {
+
+    use super::*;
+    use pretty_assertions_sorted::{assert_eq, assert_eq_sorted};
+    use crate::{get_batch_resources, get_cloudbuild_resources, test_envs::TestEnvs};
+
+    static BATCH_ENVS: [&str; 5] = ["BATCH_JOB_ID", "BATCH_JOB_UID", "BATCH_TASK_INDEX", "BATCH_TASK_COUNT", "BATCH_TASK_RETRY_ATTEMPT"];
+    static BUILD_ENVS: [&str; 5] = ["BUILD_ID", "BUILDER_OUTPUT", "PROJECT_ID", "PROJECT_NUMBER", "LOCATION"];
+
+    #[test]
+    fn test_not_running_on_batch() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(BATCH_ENVS.to_vec());
+        let metadata = serde_json::json!({});
+        assert_eq!(get_batch_resources(&DetectionContext::new(&metadata)).is_err(), true);
+    }
+
+    #[test]
+    fn test_finding_batch_resources() {
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(BATCH_ENVS.to_vec());
+
+            TestEnvs::set_var("BATCH_JOB_ID", "projects/fakeProject/locations/us-east4/jobs/nightly");
+            TestEnvs::set_var("BATCH_TASK_INDEX", "3");
+            TestEnvs::set_var("BATCH_TASK_COUNT", "10");
+
+            let metadata = serde_json::json!({
+                "project": {"projectId": "fakeProject"},
+                "instance": {
+                    "name": "fakeName",
+                    "id": "fakeId",
+                    "machineType": "fakeMachineType",
+                    "zone": "projects/233510669999/zones/us-east4-b",
+                },
+            });
+            get_batch_resources(&DetectionContext::new(&metadata)).unwrap()
         };
-        let res_default = Resource::default();
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
         let res_sould_be = Resource::new(vec![
-            KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
-            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
-            KeyValue::new("host.id", "instance_id".to_string()),
-            KeyValue::new("k8s.pod.name", "host_name".to_string()),
-            KeyValue::new("container.name", "container_name".to_string()),
-            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
-            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.batch.job.id", "projects/fakeProject/locations/us-east4/jobs/nightly".to_string()),
+            KeyValue::new("gcp.batch.task.count", 10),
+            KeyValue::new("gcp.batch.task.index", 3),
+            KeyValue::new("gcp.gce.instance.name", "fakeName".to_string()),
+            KeyValue::new("host.id", "fakeId".to_string()),
+            KeyValue::new("host.name", "fakeName".to_string()),
+            KeyValue::new("host.type", "fakeMachineType".to_string()),
         ]);
         assert_eq_sorted!(res, res_sould_be);
     }
 
     #[test]
-    fn test_finding_gke_resources_with_pod_name() {
-        let resources = {  
+    fn test_not_running_on_cloudbuild() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(BUILD_ENVS.to_vec());
+
+        TestEnvs::set_var("BUILD_ID", "other-ci-build");
+        assert_eq!(get_cloudbuild_resources(&DetectionContext::new(&serde_json::Value::Null)).is_err(), true);
+    }
+
+    #[test]
+    fn test_finding_cloudbuild_resources_without_metadata() {
+        let resources = {
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST","CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME"]);
+            let _e = TestEnvs::new(BUILD_ENVS.to_vec());
 
-            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
-            TestEnvs::set_var("NAMESPACE", "namespace");
-            TestEnvs::set_var("CONTAINER_NAME", "container_name");
-            TestEnvs::set_var("HOSTNAME", "host_name");
-            TestEnvs::set_var("POD_NAME", "pod_name");
+            TestEnvs::set_var("BUILD_ID", "0f3a8a5e-1b2c-4d5e-8f90-1234567890ab");
+            TestEnvs::set_var("BUILDER_OUTPUT", "/builder/outputs");
+            TestEnvs::set_var("PROJECT_ID", "fakeProject");
+            TestEnvs::set_var("PROJECT_NUMBER", "233510669999");
+            TestEnvs::set_var("LOCATION", "us-east4");
 
-            let metadata = GKE_RESOURCES_JSON_STRING.clone();
-            get_gke_resources(&metadata).unwrap()
+            get_cloudbuild_resources(&DetectionContext::new(&serde_json::Value::Null)).unwrap()
+        };
+        let res = Resource::new(resources);
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.cloud_build.build.id", "0f3a8a5e-1b2c-4d5e-8f90-1234567890ab".to_string()),
+            KeyValue::new("gcp.project.number", "233510669999".to_string()),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+    }
+
+    #[test]
+    fn test_finding_cloudbuild_resources_with_metadata() {
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(BUILD_ENVS.to_vec());
+
+            TestEnvs::set_var("BUILD_ID", "build");
+            TestEnvs::set_var("BUILDER_OUTPUT", "/builder/outputs");
+            TestEnvs::set_var("PROJECT_ID", "ignored");
+            TestEnvs::set_var("LOCATION", "global");
+
+            let metadata = serde_json::json!({
+                "project": {"projectId": "fakeProject"},
+                "instance": {"zone": "projects/233510669999/zones/us-east4-b"},
+            });
+            get_cloudbuild_resources(&DetectionContext::new(&metadata)).unwrap()
         };
-        let res_default = Resource::default();
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
         let res_sould_be = Resource::new(vec![
-            KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("k8s.cluster.name", "cluster_name".to_string()),
-            KeyValue::new("k8s.namespace.name", "namespace".to_string()),
-            KeyValue::new("host.id", "instance_id".to_string()),
-            KeyValue::new("k8s.pod.name", "pod_name".to_string()),
-            KeyValue::new("container.name", "container_name".to_string()),
-            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
-            KeyValue::new("gcp.resource_type", "gke_container"),
+            KeyValue::new("gcp.cloud_build.build.id", "build".to_string()),
         ]);
         assert_eq_sorted!(res, res_sould_be);
     }
-
 }
 
 #[cfg(test)]
-mod cloudfunctions_tests {
+mod vertex_ai_tests {
 
     use super::*;
     use once_cell::sync::Lazy;
-    // use pretty_assertions::{assert_eq, assert_ne};
     use pretty_assertions_sorted::{assert_eq, assert_eq_sorted};
-    use regex::Regex;
-    use crate::{get_cloudfunctions_resources, get_cloudrun_resources, test_envs::TestEnvs};
-    
-    static CLOUDFUNCTIONS_RESOURCES_JSON_STRING: Lazy<serde_json::Value> = Lazy::new(|| {
+    use crate::{get_vertex_ai_resources, test_envs::TestEnvs, GoogleCloudResourceDetector};
+    use crate::test_k8s_workload::block_on;
+
+    static VERTEX_AI_ENVS: [&str; 6] = ["AIP_DEPLOYED_MODEL_ID", "AIP_ENDPOINT_ID", "CLOUD_ML_JOB_ID", "CLUSTER_SPEC", "KUBERNETES_SERVICE_HOST", "CONTAINER_NAME"];
+    static VERTEX_AI_RESOURCES_JSON_STRING: Lazy<serde_json::Value> = Lazy::new(|| {
         serde_json::json!({
             "instance": {
                 "id": "instance_id",
-                "zone": "projects/123/zones/zone",
-                "region": "projects/123/regions/region",
+                "zone": "projects/123/zones/us-central1-a",
+                "attributes": {"cluster-name": "tenant-cluster"},
             },
             "project": {"projectId": "project_id"},
         })
     });
 
     #[test]
-    fn test_not_running_on_cloudrun() {
+    fn test_not_running_on_vertex_ai() {
         let _m = THE_RESOURCE.lock().unwrap();
-        let metadata = serde_json::json!({});
-        let resources = get_cloudrun_resources(&metadata);
-        assert_eq!(resources.is_err(), true);
+        let _e = TestEnvs::new(VERTEX_AI_ENVS.to_vec());
+        let metadata = VERTEX_AI_RESOURCES_JSON_STRING.clone();
+        assert_eq!(get_vertex_ai_resources(&DetectionContext::new(&metadata)).is_err(), true);
     }
 
     #[test]
-    fn test_missing_service_name() {
-        let resources = {  
-            let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["FUNCTION_TARGET","K_SERVICE","K_REVISION"]);
+    fn test_best_effort_vertex_ai_resources_without_project() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(VERTEX_AI_ENVS.to_vec());
+        TestEnvs::set_var("CLOUD_ML_JOB_ID", "4567");
+
+        let mut metadata = VERTEX_AI_RESOURCES_JSON_STRING.clone();
+        metadata["project"] = serde_json::json!({});
+        let strict = get_vertex_ai_resources(&DetectionContext::new(&metadata));
+        assert_eq!(strict.unwrap_err().to_string(), "project.projectId not found");
+
+        let context = DetectionContext::new(&metadata).with_best_effort(true);
+        let res = Resource::new(get_vertex_ai_resources(&context).unwrap());
+        assert_eq!(res.get("cloud.account.id".into()), None);
+        assert_eq!(res.get("gcp.resource_type".into()), Some("vertex_ai_custom_job".into()));
+        assert_eq!(context.take_missing(), vec!["project.projectId".to_string()]);
+    }
 
-            TestEnvs::set_var("FUNCTION_TARGET", "function");
-            TestEnvs::remove_var("K_SERVICE");
-            TestEnvs::remove_var("K_REVISION");
+    #[test]
+    fn test_finding_vertex_ai_prediction_resources_before_gke() {
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(VERTEX_AI_ENVS.to_vec());
 
-            let metadata = CLOUDFUNCTIONS_RESOURCES_JSON_STRING.clone();
-            get_cloudfunctions_resources(&metadata).unwrap()
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("AIP_DEPLOYED_MODEL_ID", "1234567890");
+            TestEnvs::set_var("AIP_ENDPOINT_ID", "9876543210");
+
+            // The network of the Google-managed VM isn't the one of the user
+            let mut metadata = VERTEX_AI_RESOURCES_JSON_STRING.clone();
+            metadata["instance"]["networkInterfaces"] = serde_json::json!([{"ip": "10.128.0.2", "network": "projects/123/networks/tenant"}]);
+            let builder = GoogleCloudResourceDetector::builder()
+                .with_network_attributes(true)
+                .with_fs_root(std::env::temp_dir().join("otel_gcp_empty_root"));
+            block_on(builder.detect(&metadata))
         };
-        let res_default = Resource::default();
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
         let res_sould_be = Resource::new(vec![
             KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("cloud.platform", "gcp_cloud_functions"),
-            KeyValue::new("cloud.region", "region".to_string()),
-            KeyValue::new("faas.instance", "instance_id".to_string()),
-            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-central1-a".to_string()),
+            KeyValue::new("cloud.platform", "gcp_vertex_ai"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("gcp.resource_type", "cloud_functions"),
+            KeyValue::new("cloud.region", "us-central1".to_string()),
+            KeyValue::new("gcp.resource_type", "vertex_ai_endpoint"),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("gcp.vertex_ai.deployed_model.id", "1234567890".to_string()),
+            KeyValue::new("gcp.vertex_ai.endpoint.id", "9876543210".to_string()),
         ]);
         assert_eq_sorted!(res, res_sould_be);
     }
 
     #[test]
-    fn test_environment_empty_strings() {
-        let resources = {  
+    fn test_finding_vertex_ai_training_resources() {
+        let resources = {
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["FUNCTION_TARGET","K_SERVICE","K_REVISION"]);
+            let _e = TestEnvs::new(VERTEX_AI_ENVS.to_vec());
 
-            TestEnvs::set_var("FUNCTION_TARGET", "function");
-            TestEnvs::set_var("K_SERVICE","");
-            TestEnvs::set_var("K_REVISION","");
+            TestEnvs::set_var("CLOUD_ML_JOB_ID", "4567");
+            TestEnvs::set_var("CLUSTER_SPEC", r#"{"cluster":{"workerpool0":["cmle-training-workerpool0-ab-0:2222"],"workerpool1":["cmle-training-workerpool1-ab-0:2222"]},"environment":"cloud","task":{"type":"workerpool1","index":0},"job":"{}"}"#);
 
-            let metadata = CLOUDFUNCTIONS_RESOURCES_JSON_STRING.clone();
-            get_cloudfunctions_resources(&metadata).unwrap()
+            let metadata = VERTEX_AI_RESOURCES_JSON_STRING.clone();
+            get_vertex_ai_resources(&DetectionContext::new(&metadata)).unwrap()
         };
-        let res_default = Resource::default();
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
         let res_sould_be = Resource::new(vec![
             KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("cloud.platform", "gcp_cloud_functions"),
-            KeyValue::new("cloud.region", "region".to_string()),
-            KeyValue::new("faas.instance", "instance_id".to_string()),
-            KeyValue::new("faas.name", "".to_string()),
-            KeyValue::new("faas.version", "".to_string()),
-            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-central1-a".to_string()),
+            KeyValue::new("cloud.platform", "gcp_vertex_ai"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("gcp.resource_type", "cloud_functions"),
+            KeyValue::new("cloud.region", "us-central1".to_string()),
+            KeyValue::new("gcp.resource_type", "vertex_ai_custom_job"),
+            KeyValue::new("host.id", "instance_id".to_string()),
+            KeyValue::new("gcp.vertex_ai.job.id", "4567".to_string()),
+            KeyValue::new("gcp.vertex_ai.replica.index", 0),
+            KeyValue::new("gcp.vertex_ai.replica.type", "workerpool1".to_string()),
         ]);
         assert_eq_sorted!(res, res_sould_be);
     }
 
     #[test]
-    fn test_finding_cloudfunctions_resources() {
-        let resources = {  
-            let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["FUNCTION_TARGET","K_SERVICE","K_REVISION"]);
-
-            TestEnvs::set_var("FUNCTION_TARGET", "function");
-            TestEnvs::set_var("K_SERVICE","service");
-            TestEnvs::set_var("K_REVISION","revision");
+    fn test_vertex_ai_resources_without_zone() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(VERTEX_AI_ENVS.to_vec());
+        TestEnvs::set_var("CLOUD_ML_JOB_ID", "4567");
+        let metadata = serde_json::json!({
+            "instance": {"id": "instance_id"},
+            "project": {"projectId": "project_id"},
+        });
+        let strict = get_vertex_ai_resources(&DetectionContext::new(&metadata));
+        assert_eq!(strict.unwrap_err().to_string(), "instance.zone not found");
 
-            let metadata = CLOUDFUNCTIONS_RESOURCES_JSON_STRING.clone();
-            get_cloudfunctions_resources(&metadata).unwrap()
-        };
-        let res_default = Resource::default();
-        let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
+        let context = DetectionContext::new(&metadata).with_best_effort(true);
+        let res = Resource::new(get_vertex_ai_resources(&context).unwrap());
         let res_sould_be = Resource::new(vec![
-            KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("cloud.platform", "gcp_cloud_functions"),
-            KeyValue::new("cloud.region", "region".to_string()),
-            KeyValue::new("faas.instance", "instance_id".to_string()),
-            KeyValue::new("faas.name", "service".to_string()),
-            KeyValue::new("faas.version", "revision".to_string()),
-            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.account.id", "project_id"),
+            KeyValue::new("cloud.platform", "gcp_vertex_ai"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("gcp.resource_type", "cloud_functions"),
+            KeyValue::new("gcp.resource_type", "vertex_ai_custom_job"),
+            KeyValue::new("host.id", "instance_id"),
+            KeyValue::new("gcp.vertex_ai.job.id", "4567"),
         ]);
         assert_eq_sorted!(res, res_sould_be);
+        assert_eq!(context.take_missing(), vec!["instance.zone".to_string()]);
     }
-
 }
 
 #[cfg(test)]
-mod cloudrun_tests {
+mod developer_environment_tests {
 
     use super::*;
     use once_cell::sync::Lazy;
-    // use pretty_assertions::{assert_eq, assert_ne};
     use pretty_assertions_sorted::{assert_eq, assert_eq_sorted};
-    use regex::Regex;
-    use crate::{get_cloudrun_resources, test_envs::TestEnvs};
-    static CLOUDRUN_RESOURCES_JSON_STRING: Lazy<serde_json::Value> = Lazy::new(|| {
+    use crate::{get_cloudshell_resources, get_workstations_resources, test_envs::TestEnvs, GoogleCloudResourceDetector};
+    use crate::platform::{self, PlatformDetector};
+    use crate::test_k8s_workload::block_on;
+
+    static DEV_ENVS: [&str; 7] = ["CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL", "GOOGLE_CLOUD_PROJECT", "DEVSHELL_PROJECT_ID", "KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET"];
+    static GCE_RESOURCES_JSON_STRING: Lazy<serde_json::Value> = Lazy::new(|| {
         serde_json::json!({
+            "project": {"projectId": "fakeProject"},
             "instance": {
-                "id": "instance_id",
-                "zone": "projects/123/zones/zone",
-                "region": "projects/123/regions/region",
+                "name": "fakeName",
+                "id": "fakeId",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
             },
-            "project": {"projectId": "project_id"},
         })
     });
 
     #[test]
-    fn test_not_running_on_cloudrun() {
+    fn test_not_running_on_workstations_or_cloudshell() {
         let _m = THE_RESOURCE.lock().unwrap();
-        let metadata = serde_json::json!({});
-        let resources = get_cloudrun_resources(&metadata);
-        assert_eq!(resources.is_err(), true);
+        let _e = TestEnvs::new(DEV_ENVS.to_vec());
+        let metadata = GCE_RESOURCES_JSON_STRING.clone();
+        assert_eq!(get_workstations_resources(&DetectionContext::new(&metadata)).is_err(), true);
+        assert_eq!(get_cloudshell_resources(&DetectionContext::new(&metadata)).is_err(), true);
     }
 
     #[test]
-    fn test_missing_service_name() {
-        let resources = {  
+    fn test_best_effort_workstations_resources_without_project() {
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(DEV_ENVS.to_vec());
+        TestEnvs::set_var("CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "/var/lib/cloud-workstations");
+
+        let mut metadata = GCE_RESOURCES_JSON_STRING.clone();
+        metadata["project"] = serde_json::json!({});
+        let strict = get_workstations_resources(&DetectionContext::new(&metadata));
+        assert_eq!(strict.unwrap_err().to_string(), "project.projectId not found");
+
+        let context = DetectionContext::new(&metadata).with_best_effort(true);
+        let res = Resource::new(get_workstations_resources(&context).unwrap());
+        assert_eq!(res.get("cloud.account.id".into()), None);
+        assert_eq!(res.get("cloud.platform".into()), Some("gcp_cloud_workstations".into()));
+        assert_eq!(context.take_missing(), vec!["project.projectId".to_string()]);
+    }
+
+    #[test]
+    fn test_finding_workstations_resources_not_gce() {
+        let (resources, gce) = {
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["K_CONFIGURATION","K_SERVICE","K_REVISION"]);
+            let _e = TestEnvs::new(DEV_ENVS.to_vec());
 
-            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
-            TestEnvs::remove_var("K_SERVICE");
-            TestEnvs::remove_var("K_REVISION");
+            TestEnvs::set_var("CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "/var/lib/cloud-workstations");
 
-            let metadata = CLOUDRUN_RESOURCES_JSON_STRING.clone();
-            get_cloudrun_resources(&metadata).unwrap()
+            let metadata = GCE_RESOURCES_JSON_STRING.clone();
+            (block_on(GoogleCloudResourceDetector::builder().detect(&metadata)), platform::GceDetector.detect(&DetectionContext::new(&metadata)))
         };
-        let res_default = Resource::default();
+        assert_eq!(gce.unwrap_err().to_string(), "CLOUD_WORKSTATIONS_CONFIG_DIRECTORY set");
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
         let res_sould_be = Resource::new(vec![
-            KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("cloud.platform", "gcp_cloud_run"),
-            KeyValue::new("cloud.region", "region".to_string()),
-            KeyValue::new("faas.instance", "instance_id".to_string()),
-            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.account.id", "fakeProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "us-east4-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_workstations"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("gcp.resource_type", "cloud_run"),
+            KeyValue::new("cloud.region", "us-east4".to_string()),
+            KeyValue::new("gcp.resource_type", "cloud_workstation"),
+            KeyValue::new("host.id", "fakeId".to_string()),
         ]);
         assert_eq_sorted!(res, res_sould_be);
-        
     }
 
     #[test]
-    fn test_environment_empty_strings() {
-        let resources = {  
+    fn test_finding_cloudshell_resources_without_project_metadata() {
+        let resources = {
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["K_CONFIGURATION","K_SERVICE","K_REVISION"]);
+            let _e = TestEnvs::new(DEV_ENVS.to_vec());
 
-            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
-            TestEnvs::set_var("K_SERVICE", "");
-            TestEnvs::set_var("K_REVISION", "");
+            TestEnvs::set_var("CLOUD_SHELL", "true");
+            TestEnvs::set_var("GOOGLE_CLOUD_PROJECT", "shellProject");
 
-            let metadata = CLOUDRUN_RESOURCES_JSON_STRING.clone();
-            get_cloudrun_resources(&metadata).unwrap()
+            let metadata = serde_json::json!({"instance": {"zone": "projects/233510669999/zones/europe-west1-b"}});
+            block_on(GoogleCloudResourceDetector::builder().detect(&metadata))
         };
-        let res_default = Resource::default();
         let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
         let res_sould_be = Resource::new(vec![
-            KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("cloud.platform", "gcp_cloud_run"),
-            KeyValue::new("cloud.region", "region".to_string()),
-            KeyValue::new("faas.instance", "instance_id".to_string()),
-            KeyValue::new("faas.name", "".to_string()),
-            KeyValue::new("faas.version", "".to_string()),
-            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.account.id", "shellProject".to_string()),
+            KeyValue::new("cloud.availability_zone", "europe-west1-b".to_string()),
+            KeyValue::new("cloud.platform", "gcp_cloud_shell"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("gcp.resource_type", "cloud_run"),
+            KeyValue::new("cloud.region", "europe-west1".to_string()),
+            KeyValue::new("gcp.resource_type", "cloud_shell"),
         ]);
         assert_eq_sorted!(res, res_sould_be);
-        
     }
 
     #[test]
-    fn test_finding_cloudrun_resources() {
-        let resources = {  
+    fn test_local_fallback_from_gcloud_config() {
+        let config_dir = std::env::temp_dir().join(format!("otel_gcp_gcloud_config_{}", std::process::id()));
+        std::fs::create_dir_all(config_dir.join("configurations")).unwrap();
+        std::fs::write(config_dir.join("active_config"), "dev\n").unwrap();
+        std::fs::write(config_dir.join("configurations").join("config_dev"), "[core]\naccount = dev@example.com\nproject = dev-project\n").unwrap();
+
+        let (enabled, disabled) = {
             let _m = THE_RESOURCE.lock().unwrap();
-            let _e = TestEnvs::new(vec!["K_CONFIGURATION","K_SERVICE","K_REVISION"]);
+            let _e = TestEnvs::new(vec!["GOOGLE_CLOUD_PROJECT", "GOOGLE_APPLICATION_CREDENTIALS", "CLOUDSDK_CORE_PROJECT", "CLOUDSDK_ACTIVE_CONFIG_NAME", "CLOUDSDK_CONFIG", "HOSTNAME", "BUILD_ID", "KUBERNETES_SERVICE_HOST"]);
+
+            TestEnvs::set_var("CLOUDSDK_CONFIG", config_dir.to_str().unwrap());
+            TestEnvs::set_var("HOSTNAME", "laptop");
+            (
+                block_on(GoogleCloudResourceDetector::builder().with_local_fallback(true).detect(&serde_json::Value::Null)),
+                block_on(GoogleCloudResourceDetector::builder().detect(&serde_json::Value::Null)),
+            )
+        };
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("cloud.account.id", "dev-project"),
+            KeyValue::new("cloud.provider", "gcp"),
+            KeyValue::new("gcp.resource_type", "generic_node"),
+            KeyValue::new("host.name", "laptop"),
+        ]);
+        assert_eq_sorted!(Resource::new(enabled), res_sould_be);
+        assert!(disabled.is_empty());
+    }
 
-            TestEnvs::set_var("K_CONFIGURATION", "cloudrun_config");
-            TestEnvs::set_var("K_SERVICE", "service");
-            TestEnvs::set_var("K_REVISION", "revision");
+    #[test]
+    fn test_local_fallback_prefers_env_and_credentials() {
+        let credentials = std::env::temp_dir().join(format!("otel_gcp_adc_{}.json", std::process::id()));
+        std::fs::write(&credentials, r#"{"type": "service_account", "project_id": "sa-project"}"#).unwrap();
 
-            let metadata = CLOUDRUN_RESOURCES_JSON_STRING.clone();
-            get_cloudrun_resources(&metadata).unwrap()
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(vec!["GOOGLE_CLOUD_PROJECT", "GOOGLE_APPLICATION_CREDENTIALS", "CLOUDSDK_CORE_PROJECT", "CLOUDSDK_CONFIG", "HOSTNAME", "BUILD_ID", "KUBERNETES_SERVICE_HOST"]);
+        TestEnvs::set_var("HOSTNAME", "laptop");
+        TestEnvs::set_var("CLOUDSDK_CORE_PROJECT", "gcloud-project");
+        TestEnvs::set_var("GOOGLE_APPLICATION_CREDENTIALS", credentials.to_str().unwrap());
+
+        let builder = GoogleCloudResourceDetector::builder().with_local_fallback(true);
+        let res = Resource::new(block_on(builder.detect(&serde_json::Value::Null)));
+        assert_eq!(res.get("cloud.account.id".into()), Some("sa-project".into()));
+
+        TestEnvs::set_var("GOOGLE_CLOUD_PROJECT", "env-project");
+        let builder = GoogleCloudResourceDetector::builder().with_local_fallback(true);
+        let res = Resource::new(block_on(builder.detect(&serde_json::Value::Null)));
+        assert_eq!(res.get("cloud.account.id".into()), Some("env-project".into()));
+    }
+}
+
+#[cfg(test)]
+mod platform_detector_tests {
+
+    use super::*;
+    use pretty_assertions_sorted::{assert_eq, assert_eq_sorted};
+    use crate::error::OpenTelemetryError;
+    use crate::platform::{self, DetectionContext, PlatformDetector};
+    use crate::report::PlatformAttempt;
+    use crate::{test_envs::TestEnvs, GoogleCloudResourceDetector};
+    use crate::test_k8s_workload::block_on;
+
+    /// Internal PaaS running on GKE, identified by its own environment variable
+    struct InternalPaasDetector;
+
+    impl PlatformDetector for InternalPaasDetector {
+        fn name(&self) -> &str {
+            "internal_paas"
+        }
+
+        fn detect(&self, context: &DetectionContext) -> Result<Vec<KeyValue>, OpenTelemetryError> {
+            let app = if let Ok(app) = std::env::var("PAAS_APP") {
+                app
+            } else {
+                Err(OpenTelemetryError::new("PAAS_APP not found"))?
+            };
+            let mut attrs = platform::GkeDetector.detect(context)?;
+            attrs.push(KeyValue::new("service.name", app));
+            attrs.push(KeyValue::new("gcp.resource_type", "internal_paas"));
+            Ok(attrs)
+        }
+    }
+
+    fn gke_metadata() -> serde_json::Value {
+        serde_json::json!({
+            "project": {"projectId": "project_id"},
+            "instance": {
+                "id": "instance_id",
+                "zone": "projects/233510669999/zones/us-east4-b",
+                "attributes": {"cluster-name": "cluster_name", "cluster-location": "us-east4"},
+            },
+        })
+    }
+
+    #[test]
+    fn test_missing_rejects_without_metadata_unless_best_effort() {
+        let context = DetectionContext::new(&serde_json::Value::Null);
+        assert_eq!(context.missing("instance.zone").unwrap_err().to_string(), "instance.zone not found");
+
+        let context = DetectionContext::new(&serde_json::Value::Null).with_best_effort(true);
+        assert!(context.missing("instance.zone").is_ok());
+        assert_eq!(context.take_missing(), vec!["instance.zone".to_string()]);
+    }
+
+    #[test]
+    fn test_user_defined_detector_first() {
+        let metadata = gke_metadata();
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME", "K_SERVICE", "K_REVISION", "PAAS_APP"]);
+
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("POD_NAME", "pod_name");
+            TestEnvs::set_var("PAAS_APP", "checkout");
+
+            let mut detectors = platform::default_detectors();
+            detectors.insert(0, Box::new(InternalPaasDetector));
+            let builder = GoogleCloudResourceDetector::builder().with_platform_detectors(detectors);
+            block_on(builder.detect(&metadata))
         };
-        let res_default = Resource::default();
-        let res = Resource::new(resources);
-        // let res = res.merge(&res_default);
-        // res.iter().for_each(|kv| println!("{:?} : {:?}", kv.0, kv.1));
-        
+
         let res_sould_be = Resource::new(vec![
-            KeyValue::new("cloud.account.id", "project_id".to_string()),
-            KeyValue::new("cloud.platform", "gcp_cloud_run"),
-            KeyValue::new("cloud.region", "region".to_string()),
-            KeyValue::new("faas.instance", "instance_id".to_string()),
-            KeyValue::new("faas.name", "service".to_string()),
-            KeyValue::new("faas.version", "revision".to_string()),
-            KeyValue::new("cloud.zone", "zone".to_string()),
+            KeyValue::new("cloud.account.id", "project_id"),
+            KeyValue::new("cloud.platform", "gcp_kubernetes_engine"),
             KeyValue::new("cloud.provider", "gcp"),
-            KeyValue::new("gcp.resource_type", "cloud_run"),
+            KeyValue::new("cloud.region", "us-east4"),
+            KeyValue::new("cloud.zone", "us-east4-b"),
+            KeyValue::new("gcp.resource_type", "internal_paas"),
+            KeyValue::new("host.id", "instance_id"),
+            KeyValue::new("k8s.cluster.name", "cluster_name"),
+            KeyValue::new("k8s.namespace.name", "namespace"),
+            KeyValue::new("k8s.pod.name", "pod_name"),
+            KeyValue::new("service.name", "checkout"),
         ]);
-        assert_eq_sorted!(res, res_sould_be);
-        
+        assert_eq_sorted!(Resource::new(resources), res_sould_be);
+    }
+
+    #[test]
+    fn test_disabled_and_reordered_detectors() {
+        let metadata = gke_metadata();
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "K_CONFIGURATION", "FUNCTION_TARGET", "CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+        TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+
+        // Without the GKE detector nothing matches, the metadata has no GCE machine type
+        let detectors = platform::default_detectors().into_iter().filter(|detector| detector.name() != "gke").collect();
+        let builder = GoogleCloudResourceDetector::builder().with_platform_detectors(detectors);
+        assert!(block_on(builder.detect(&metadata)).is_empty());
+
+        let builder = GoogleCloudResourceDetector::builder().with_platform_detectors(vec![]);
+        assert!(block_on(builder.detect(&metadata)).is_empty());
+
+        let names: Vec<String> = platform::default_detectors().iter().map(|detector| detector.name().to_string()).collect();
+        assert_eq!(names.first().map(String::as_str), Some("vertex_ai"));
+        assert!(names.iter().position(|name| name == "gke") < names.iter().position(|name| name == "gce"));
+        assert!(!names.contains(&"self_managed_k8s".to_string()));
+        assert!(!names.contains(&"local".to_string()));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "the opt-in detectors are ignored with custom platform detectors")]
+    fn test_platform_detectors_with_opt_in() {
+        let builder = GoogleCloudResourceDetector::builder()
+            .with_platform_detectors(platform::default_detectors())
+            .with_self_managed_k8s(true);
+        block_on(builder.detect(&serde_json::Value::Null));
+    }
+
+    #[test]
+    fn test_platform_detectors_with_added_opt_in() {
+        let metadata = serde_json::json!({
+            "project": {"projectId": "project_id"},
+            "instance": {
+                "name": "worker-1",
+                "id": "instance_id",
+                "machineType": "fakeMachineType",
+                "zone": "projects/233510669999/zones/us-east4-b",
+            },
+        });
+        let resources = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "CONTAINER_NAME", "NAMESPACE", "HOSTNAME", "POD_NAME", "NODE_NAME", "K8S_CLUSTER_NAME", "K_SERVICE", "K_REVISION"]);
+            TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+            TestEnvs::set_var("NAMESPACE", "namespace");
+            TestEnvs::set_var("POD_NAME", "pod_name");
+
+            let detectors: Vec<Box<dyn PlatformDetector>> = vec![
+                Box::new(platform::SelfManagedK8sDetector { cluster_name: Some("kubeadm-cluster".to_string()) }),
+                Box::new(platform::GceDetector),
+            ];
+            let builder = GoogleCloudResourceDetector::builder()
+                .with_platform_detectors(detectors)
+                .with_fs_root(std::env::temp_dir().join("otel_gcp_empty_root"));
+            block_on(builder.detect(&metadata))
+        };
+        let res = Resource::new(resources);
+        assert_eq!(res.get("k8s.cluster.name".into()), Some("kubeadm-cluster".into()));
+        assert_eq!(res.get("k8s.pod.name".into()), Some("pod_name".into()));
+    }
+
+    #[test]
+    fn test_detection_report() {
+        let metadata = gke_metadata();
+        let _m = THE_RESOURCE.lock().unwrap();
+        let _e = TestEnvs::new(vec!["KUBERNETES_SERVICE_HOST", "AIP_DEPLOYED_MODEL_ID", "CLOUD_ML_JOB_ID", "CLOUD_WORKSTATIONS_CONFIG_DIRECTORY", "CLOUD_SHELL"]);
+
+        let detectors: Vec<Box<dyn PlatformDetector>> = vec![
+            Box::new(platform::VertexAiDetector),
+            Box::new(platform::GkeDetector),
+            Box::new(platform::TpuDetector),
+            Box::new(platform::DataprocDetector),
+            Box::new(platform::GceDetector),
+        ];
+        let builder = GoogleCloudResourceDetector::builder().with_platform_detectors(detectors);
+        let (resources, report) = block_on(builder.detect_with_report(&metadata));
+        assert!(resources.is_empty());
+        assert_eq!(report.platform(), None);
+        assert_eq!(
+            report.attempts,
+            vec![
+                PlatformAttempt { platform: "vertex_ai".to_string(), rejection: Some("AIP_DEPLOYED_MODEL_ID and CLOUD_ML_JOB_ID not found".to_string()), missing: vec![] },
+                PlatformAttempt { platform: "gke".to_string(), rejection: Some("KUBERNETES_SERVICE_HOST not found".to_string()), missing: vec![] },
+                PlatformAttempt { platform: "tpu".to_string(), rejection: Some("instance.attributes.accelerator-type not found".to_string()), missing: vec![] },
+                PlatformAttempt { platform: "dataproc".to_string(), rejection: Some("instance.attributes.dataproc-cluster-name not found".to_string()), missing: vec![] },
+                PlatformAttempt { platform: "gce".to_string(), rejection: Some("instance.machineType not found".to_string()), missing: vec![] },
+            ]
+        );
+
+        TestEnvs::set_var("KUBERNETES_SERVICE_HOST", "10.0.0.1");
+        let (resources, report) = block_on(builder.detect_with_report(&metadata));
+        assert!(!resources.is_empty());
+        assert_eq!(report.platform(), Some("gke"));
+        assert_eq!(report.attempts.len(), 2);
+    }
+
+    #[test]
+    fn test_detection_report_of_simulated_platform() {
+        let detector = {
+            let _m = THE_RESOURCE.lock().unwrap();
+            let _e = TestEnvs::new(vec!["OTEL_GCP_PLATFORM"]);
+            TestEnvs::set_var("OTEL_GCP_PLATFORM", "gce");
+            block_on(GoogleCloudResourceDetector::builder().build())
+        };
+        let report = detector.report();
+        assert_eq!(report.simulated_platform.as_deref(), Some("gce"));
+        assert_eq!(report.metadata_fetch_duration, None);
+        assert_eq!(report.platform(), Some("gce"));
+        assert!(report.to_string().starts_with("metadata not fetched, simulated platform gce, detected platform: gce"));
+        assert_eq!(detector.get_resource().get("gcp.simulated".into()), Some(true.into()));
     }
 }
-        
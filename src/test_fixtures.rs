@@ -0,0 +1,29 @@
+use std::{fs, path::PathBuf};
+
+/// Fixture directory under the system temp dir, removed with its content when dropped
+pub struct FixtureDir {
+    pub path: PathBuf,
+}
+
+impl FixtureDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("otel_gcp_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    /// Write `content` to `relative_path`, creating the parent directories
+    pub fn write(&self, relative_path: &str, content: &str) -> PathBuf {
+        let path = self.path.join(relative_path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for FixtureDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
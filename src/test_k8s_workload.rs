@@ -0,0 +1,199 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+/// Minimal kubernetes api server answering GET requests with canned json bodies,
+/// a `null` body answers `403 Forbidden`
+pub struct FakeApiServer {
+    pub url: String,
+}
+
+impl FakeApiServer {
+    pub fn start(token: Option<&str>, routes: Vec<(&str, serde_json::Value)>) -> Self {
+        Self::start_with_delay(token, routes, Duration::ZERO)
+    }
+
+    /// Like [`FakeApiServer::start`], waiting `delay` before answering each request
+    pub fn start_with_delay(token: Option<&str>, routes: Vec<(&str, serde_json::Value)>, delay: Duration) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let token = token.map(|t| format!("Bearer {}", t));
+        let routes: HashMap<String, String> = routes.into_iter().map(|(path, body)| (path.to_string(), body.to_string())).collect();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut path = String::new();
+                let mut authorization = None;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                    let trimmed = line.trim_end();
+                    if trimmed.is_empty() {
+                        break;
+                    }
+                    if let Some(request_path) = trimmed.strip_prefix("GET ") {
+                        path = request_path.split(' ').next().unwrap_or_default().to_string();
+                    }
+                    if let Some((name, value)) = trimmed.split_once(": ") {
+                        if name.eq_ignore_ascii_case("authorization") {
+                            authorization = Some(value.to_string());
+                        }
+                    }
+                    line.clear();
+                }
+                let (status, body) = if token.is_some() && authorization != token {
+                    ("401 Unauthorized", "{}".to_string())
+                } else if routes.get(&path).is_some_and(|body| body == "null") {
+                    ("403 Forbidden", "{}".to_string())
+                } else if let Some(body) = routes.get(&path) {
+                    ("200 OK", body.clone())
+                } else {
+                    ("404 Not Found", "{}".to_string())
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                thread::sleep(delay);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        Self { url }
+    }
+}
+
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(future)
+}
+
+pub fn owned_by(kind: &str, name: &str) -> serde_json::Value {
+    serde_json::json!({
+        "metadata": {
+            "ownerReferences": [
+                {"apiVersion": "v1", "kind": kind, "name": name, "controller": true},
+            ],
+        },
+    })
+}
+
+pub fn write_token(name: &str, token: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("otel_gcp_k8s_token_{}_{}", std::process::id(), name));
+    std::fs::write(&path, token).unwrap();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::Resource;
+    use pretty_assertions_sorted::assert_eq_sorted;
+
+    use super::*;
+    use crate::k8s_workload::{get_workload_from_pod_name, get_workload_resources, K8sApiConfig};
+
+    #[test]
+    fn test_deployment_from_api() {
+        let server = FakeApiServer::start(Some("token"), vec![
+            ("/api/v1/namespaces/ns/pods/web-7d4b9c6f5d-x2x9z", owned_by("ReplicaSet", "web-7d4b9c6f5d")),
+            ("/apis/apps/v1/namespaces/ns/replicasets/web-7d4b9c6f5d", owned_by("Deployment", "web")),
+        ]);
+        let config = K8sApiConfig::new(&server.url).with_token_path(write_token("deployment", "token\n"));
+        let resources = block_on(get_workload_resources(&config, "ns", "web-7d4b9c6f5d-x2x9z"));
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("k8s.deployment.name", "web"),
+            KeyValue::new("k8s.replicaset.name", "web-7d4b9c6f5d"),
+        ]);
+        assert_eq_sorted!(Resource::new(resources), res_sould_be);
+    }
+
+    #[test]
+    fn test_cronjob_from_api() {
+        let server = FakeApiServer::start(None, vec![
+            ("/api/v1/namespaces/ns/pods/report-abc", owned_by("Job", "report-28456789")),
+            ("/apis/batch/v1/namespaces/ns/jobs/report-28456789", owned_by("CronJob", "report")),
+        ]);
+        let config = K8sApiConfig::new(&server.url);
+        let resources = block_on(get_workload_resources(&config, "ns", "report-abc"));
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("k8s.cronjob.name", "report"),
+            KeyValue::new("k8s.job.name", "report-28456789"),
+        ]);
+        assert_eq_sorted!(Resource::new(resources), res_sould_be);
+    }
+
+    #[test]
+    fn test_daemonset_from_api() {
+        let server = FakeApiServer::start(None, vec![
+            ("/api/v1/namespaces/ns/pods/agent-x2x9z", owned_by("DaemonSet", "agent")),
+        ]);
+        let config = K8sApiConfig::new(&server.url);
+        let resources = block_on(get_workload_resources(&config, "ns", "agent-x2x9z"));
+        assert_eq_sorted!(Resource::new(resources), Resource::new(vec![KeyValue::new("k8s.daemonset.name", "agent")]));
+    }
+
+    #[test]
+    fn test_access_denied_falls_back_to_pod_name() {
+        let server = FakeApiServer::start(Some("token"), vec![
+            ("/api/v1/namespaces/ns/pods/db-0", owned_by("StatefulSet", "not-used")),
+        ]);
+        let config = K8sApiConfig::new(&server.url).with_token_path(write_token("denied", "wrong"));
+        let resources = block_on(get_workload_resources(&config, "ns", "db-0"));
+        assert_eq_sorted!(Resource::new(resources), Resource::new(vec![KeyValue::new("k8s.statefulset.name", "db")]));
+    }
+
+    #[test]
+    fn test_access_denied_to_replicaset_keeps_pod_owner() {
+        let server = FakeApiServer::start(None, vec![
+            ("/api/v1/namespaces/ns/pods/web-5f8d9b7c6d-q8z2x", owned_by("ReplicaSet", "web-5f8d9b7c6d")),
+            ("/apis/apps/v1/namespaces/ns/replicasets/web-5f8d9b7c6d", serde_json::Value::Null),
+        ]);
+        let config = K8sApiConfig::new(&server.url);
+        let resources = block_on(get_workload_resources(&config, "ns", "web-5f8d9b7c6d-q8z2x"));
+        assert_eq_sorted!(Resource::new(resources), Resource::new(vec![KeyValue::new("k8s.replicaset.name", "web-5f8d9b7c6d")]));
+    }
+
+    #[test]
+    fn test_timeout_covers_all_requests() {
+        // Each request fits in the timeout but both together don't, the deployment lookup times out
+        let server = FakeApiServer::start_with_delay(None, vec![
+            ("/api/v1/namespaces/ns/pods/web-7d4b9c6f5d-x2x9z", owned_by("ReplicaSet", "web-7d4b9c6f5d")),
+            ("/apis/apps/v1/namespaces/ns/replicasets/web-7d4b9c6f5d", owned_by("Deployment", "web")),
+        ], Duration::from_millis(400));
+        let config = K8sApiConfig::new(&server.url).with_timeout(Duration::from_millis(600));
+        let resources = block_on(get_workload_resources(&config, "ns", "web-7d4b9c6f5d-x2x9z"));
+        assert_eq_sorted!(Resource::new(resources), Resource::new(vec![KeyValue::new("k8s.replicaset.name", "web-7d4b9c6f5d")]));
+    }
+
+    #[test]
+    fn test_workload_from_pod_name() {
+        let res = Resource::new(get_workload_from_pod_name("my-app-5f8d9b7c6d-q8z2x"));
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("k8s.deployment.name", "my-app"),
+            KeyValue::new("k8s.replicaset.name", "my-app-5f8d9b7c6d"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+
+        let res = Resource::new(get_workload_from_pod_name("backup-28456790-b2k4f"));
+        let res_sould_be = Resource::new(vec![
+            KeyValue::new("k8s.cronjob.name", "backup"),
+            KeyValue::new("k8s.job.name", "backup-28456790"),
+        ]);
+        assert_eq_sorted!(res, res_sould_be);
+
+        let res = Resource::new(get_workload_from_pod_name("redis-12"));
+        assert_eq_sorted!(res, Resource::new(vec![KeyValue::new("k8s.statefulset.name", "redis")]));
+
+        assert!(get_workload_from_pod_name("fluentbit-x2x9z").is_empty());
+        assert!(get_workload_from_pod_name("standalone").is_empty());
+    }
+}
@@ -197,6 +197,121 @@ mod tests {
         assert_eq_sorted!(monitored_resource.unwrap(), export_monitored_resource);
     }
 
+    #[test]
+    fn test_get_monitored_resource_gce_instance() {
+        let res = Resource::new(vec![
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.availability_zone", "myavailzone"),
+            KeyValue::new("host.id", "myhostid"),
+        ]);
+        let monitored_resource = get_monitored_resource(res);
+        assert!(monitored_resource.is_some());
+        let export_monitored_resource = MonitoredResourceData {
+            r#type: "gce_instance".to_string(),
+            labels: to_labels(json!({
+                "instance_id": "myhostid",
+                "zone": "myavailzone",
+            })),
+        };
+        assert_eq_sorted!(monitored_resource.unwrap(), export_monitored_resource);
+    }
+
+    #[test]
+    fn test_get_monitored_resource_self_managed_k8s_container() {
+        let res = Resource::new(vec![
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.availability_zone", "myavailzone"),
+            KeyValue::new("host.id", "myhostid"),
+            KeyValue::new("k8s.cluster.name", "mycluster"),
+            KeyValue::new("k8s.namespace.name", "myns"),
+            KeyValue::new("k8s.pod.name", "mypod"),
+            KeyValue::new("k8s.container.name", "mycontainer"),
+        ]);
+        let monitored_resource = get_monitored_resource(res);
+        assert!(monitored_resource.is_some());
+        let export_monitored_resource = MonitoredResourceData {
+            r#type: "k8s_container".to_string(),
+            labels: to_labels(json!({
+                "cluster_name": "mycluster",
+                "container_name": "mycontainer",
+                "location": "myavailzone",
+                "namespace_name": "myns",
+                "pod_name": "mypod",
+            })),
+        };
+        assert_eq_sorted!(monitored_resource.unwrap(), export_monitored_resource);
+    }
+
+    #[test]
+    fn test_get_monitored_resource_self_managed_k8s_pod() {
+        let res = Resource::new(vec![
+            KeyValue::new("cloud.platform", "gcp_compute_engine"),
+            KeyValue::new("cloud.availability_zone", "myavailzone"),
+            KeyValue::new("host.id", "myhostid"),
+            KeyValue::new("k8s.cluster.name", "mycluster"),
+            KeyValue::new("k8s.namespace.name", "myns"),
+            KeyValue::new("k8s.pod.name", "mypod"),
+        ]);
+        let monitored_resource = get_monitored_resource(res);
+        assert!(monitored_resource.is_some());
+        let export_monitored_resource = MonitoredResourceData {
+            r#type: "k8s_pod".to_string(),
+            labels: to_labels(json!({
+                "cluster_name": "mycluster",
+                "location": "myavailzone",
+                "namespace_name": "myns",
+                "pod_name": "mypod",
+            })),
+        };
+        assert_eq_sorted!(monitored_resource.unwrap(), export_monitored_resource);
+    }
+
+    #[test]
+    fn test_get_monitored_resource_vertex_ai_endpoint() {
+        let res = Resource::new(vec![
+            KeyValue::new("cloud.platform", "gcp_vertex_ai"),
+            KeyValue::new("cloud.availability_zone", "us-central1-a"),
+            KeyValue::new("gcp.resource_type", "vertex_ai_endpoint"),
+            KeyValue::new("gcp.vertex_ai.endpoint.id", "9876543210"),
+            KeyValue::new("gcp.vertex_ai.deployed_model.id", "1234567890"),
+            KeyValue::new("host.id", "myhost"),
+        ]);
+        let monitored_resource = get_monitored_resource(res);
+        let export_monitored_resource = MonitoredResourceData {
+            r#type: "generic_task".to_string(),
+            labels: to_labels(json!({
+                "location": "us-central1-a",
+                "namespace": "9876543210",
+                "job": "1234567890",
+                "task_id": "myhost",
+            })),
+        };
+        assert_eq_sorted!(monitored_resource.unwrap(), export_monitored_resource);
+    }
+
+    #[test]
+    fn test_get_monitored_resource_vertex_ai_custom_job() {
+        let res = Resource::new(vec![
+            KeyValue::new("cloud.platform", "gcp_vertex_ai"),
+            KeyValue::new("cloud.region", "us-central1"),
+            KeyValue::new("gcp.resource_type", "vertex_ai_custom_job"),
+            KeyValue::new("gcp.vertex_ai.job.id", "4567"),
+            KeyValue::new("gcp.vertex_ai.replica.type", "workerpool1"),
+            KeyValue::new("host.id", "myhost"),
+        ]);
+        let monitored_resource = get_monitored_resource(res);
+        let export_monitored_resource = MonitoredResourceData {
+            r#type: "generic_task".to_string(),
+            labels: to_labels(json!({
+                "location": "us-central1",
+                "namespace": "4567",
+                "job": "workerpool1",
+                "task_id": "myhost",
+            })),
+        };
+        assert_eq_sorted!(monitored_resource.unwrap(), export_monitored_resource);
+    }
+
     #[test]
     fn test_get_monitored_resource_aws_ec2() {
         let res = Resource::new(vec![